@@ -5,14 +5,32 @@ use std::alloc::System;
 static A: System = System;
 
 use anyhow::{Context, Result};
+use clap::ArgMatches;
 use cli::Kind;
 use colored::Colorize;
-use elm_json::cli;
+use elm_json::{cli, package::retriever};
+use std::{path::PathBuf, time::Duration};
 use tracing::Level;
 use tracing_subscriber::{self, filter::LevelFilter, layer::SubscriberExt};
 
 fn main() {
-    if let Err(e) = run() {
+    let matches = cli::build().get_matches();
+
+    if let Err(e) = run(&matches) {
+        // Only the exit status matters for these, anything worth saying was said already.
+        if Kind::of(&e) == Kind::Unavailable {
+            std::process::exit(1);
+        }
+
+        if matches.is_present("json") {
+            let report = cli::error::Report::from(&e);
+            eprintln!(
+                "{}",
+                serde_json::to_string(&report).expect("Failed to serialize error")
+            );
+            std::process::exit(1);
+        }
+
         eprintln!(
             "\n{}\n",
             cli::util::format_header(&e.to_string().to_uppercase()).red()
@@ -20,19 +38,27 @@ fn main() {
         e.source()
             .map(|e| eprintln!("{}", textwrap::fill(&e.to_string(), 80)))
             .unwrap_or(());
+
+        if matches.is_present("explain") {
+            eprintln!();
+            let _ = cli::util::explain_conflict(&e);
+        }
         std::process::exit(1);
     }
 }
 
-fn run() -> Result<()> {
+fn run(matches: &ArgMatches) -> Result<()> {
+    // The first Ctrl-C lets whatever is running stop at a safe point, a second one exits
+    // immediately.
     ctrlc::set_handler(move || {
         let term = console::Term::stdout();
         let _ = term.show_cursor();
+        if elm_json::interrupt::request() {
+            std::process::exit(130);
+        }
     })
     .context(Kind::Unknown)?;
 
-    let matches = cli::build().get_matches();
-
     let min_level = match matches.occurrences_of("verbose") {
         0 => Level::WARN,
         1 => Level::INFO,
@@ -40,21 +66,59 @@ fn run() -> Result<()> {
         _ => Level::TRACE,
     };
 
+    let json_logs = matches.value_of("log-format") == Some("json");
     let subscriber = tracing_subscriber::registry::Registry::default()
         .with(LevelFilter::from_level(min_level))
-        .with(tracing_subscriber::fmt::Layer::default());
+        .with((!json_logs).then(tracing_subscriber::fmt::Layer::default))
+        .with(json_logs.then(|| tracing_subscriber::fmt::layer().json()));
     tracing::subscriber::set_global_default(subscriber).expect("Failed to set global subscriber");
 
-    let offline = matches.is_present("offline");
+    // Colors from elm-json.toml still win, since that's as explicit as a flag.
+    let noninteractive = cli::noninteractive();
+    if noninteractive {
+        colored::control::set_override(false);
+    }
+
+    let config = cli::config::Config::load()?;
+    let offline = matches.is_present("offline")
+        || (!matches.is_present("online") && config.offline.unwrap_or(false));
+    let registry_file = matches
+        .value_of("registry-file")
+        .map(PathBuf::from)
+        .or_else(|| config.registry_file.clone());
+    config.install();
+
+    let options = retriever::Options {
+        offline: offline || matches.is_present("locked"),
+        locked: matches.is_present("locked"),
+        registry_file,
+        prefer_cached: matches.is_present("prefer-cached"),
+        memoize: matches.is_present("memoize"),
+        refresh: matches.is_present("refresh"),
+        progress: !matches.is_present("no-progress") && !noninteractive,
+        max_age: matches
+            .value_of("max-age")
+            .map(|age| cli::util::parse_duration(age).expect("Invalid --max-age")),
+        solve_timeout: matches
+            .value_of("solve-timeout")
+            .map(|secs| Duration::from_secs(secs.parse().expect("Invalid --solve-timeout"))),
+    };
 
     match matches.subcommand() {
-        ("solve", Some(matches)) => cli::solve::run(matches, offline),
-        ("upgrade", Some(matches)) => cli::upgrade::run(matches, offline),
-        ("install", Some(matches)) => cli::install::run(matches, offline),
-        ("uninstall", Some(matches)) => cli::uninstall::run(matches, offline),
-        ("new", Some(matches)) => cli::new::run(matches),
+        ("solve", Some(matches)) => cli::solve::run(matches, &options),
+        ("upgrade", Some(matches)) => cli::upgrade::run(matches, &options),
+        ("install", Some(matches)) => cli::install::run(matches, &options),
+        ("uninstall", Some(matches)) => cli::uninstall::run(matches, &options),
+        ("new", Some(matches)) => cli::new::run(matches, &options),
+        ("bump", Some(matches)) => cli::bump::run(matches, &options),
+        ("rename", Some(matches)) => cli::rename::run(matches, &options),
+        ("convert", Some(matches)) => cli::convert::run(matches, &options),
+        ("info", Some(matches)) => cli::info::run(matches, &options),
+        ("search", Some(matches)) => cli::search::run(matches, &options),
+        ("available", Some(matches)) => cli::available::run(matches, &options),
         ("completions", Some(matches)) => cli::completions::run(matches),
-        ("tree", Some(matches)) => cli::tree::run(matches, offline),
+        ("tree", Some(matches)) => cli::tree::run(matches, &options),
+        ("validate", Some(matches)) => cli::validate::run(matches, &options),
         (cmd, matches) => panic!(
             "Received command {} with matches {:#?} but I don't know how to handle this",
             cmd, matches
@@ -0,0 +1,35 @@
+use super::{error::NetworkContext, util, Kind};
+use crate::{
+    package::retriever::{Options, Retriever},
+    semver::Constraint,
+};
+use anyhow::{anyhow, Context, Result};
+use clap::ArgMatches;
+
+/// Exits with a non-zero status when the version isn't available, so scripts can use it as a
+/// condition. Nothing is printed unless `--json` is passed.
+pub fn run(matches: &ArgMatches, options: &Options) -> Result<()> {
+    let (name, version) = util::parse_exact_package(matches.value_of("PACKAGE").unwrap())
+        .context(Kind::InvalidArgument)?;
+    let retriever = Retriever::new(&Constraint::any(), options).network_context(Kind::Unknown)?;
+    retriever.finish_progress();
+
+    let available = retriever.is_available(&name, &version);
+
+    if matches.is_present("json") {
+        println!(
+            "{}",
+            serde_json::json!({
+                "package": name.to_string(),
+                "version": version.to_string(),
+                "available": available,
+            })
+        );
+    }
+
+    if available {
+        Ok(())
+    } else {
+        Err(anyhow!("{}@{} is not available", name, version)).context(Kind::Unavailable)
+    }
+}
@@ -0,0 +1,49 @@
+use super::{util, Kind};
+use crate::{
+    package::retriever::Options,
+    project::{Package, Project},
+};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub fn run(matches: &ArgMatches, options: &Options) -> Result<()> {
+    if util::elm_json_type(matches)? == "application" {
+        return Err(anyhow!(
+            "Applications don't have a version, so only the elm.json of a package can be bumped."
+        ))
+        .context(Kind::NotSupported);
+    }
+
+    util::with_elm_json(
+        matches,
+        options,
+        |_, _, _| bail!(Kind::NotSupported),
+        bump_package,
+    )
+}
+
+fn bump_package(matches: &ArgMatches, _options: &Options, info: Package) -> Result<()> {
+    let current = info.version();
+    let next = match matches.value_of("PART").unwrap() {
+        "major" => current.next_major(),
+        "minor" => current.next_minor(),
+        _ => current.next_patch(),
+    };
+
+    println!(
+        "I'll bump the version from {} to {}.",
+        current.to_string().red(),
+        next.to_string().green()
+    );
+
+    let updated = Project::Package(info.with_version(next));
+    if util::confirm("Should I make these changes?", matches)? {
+        util::write_elm_json(&updated, matches)?;
+        println!("Saved updated elm.json!");
+    } else {
+        println!("Aborting!");
+    }
+
+    Ok(())
+}
@@ -1,9 +1,38 @@
-use anyhow::Result;
-use clap::ArgMatches;
-use std::io;
+use super::Kind;
+use anyhow::{anyhow, Context, Result};
+use clap::{ArgMatches, Shell};
+use std::{fs, io};
 
 pub fn run(matches: &ArgMatches) -> Result<()> {
-    let shell = matches.value_of("SHELL").unwrap();
-    super::build().gen_completions_to("elm-json", shell.parse().unwrap(), &mut io::stdout());
+    let shells: Vec<Shell> = if matches.is_present("all") {
+        Shell::variants()
+            .iter()
+            .map(|shell| shell.parse())
+            .collect::<std::result::Result<_, _>>()
+    } else {
+        matches
+            .value_of("SHELL")
+            .unwrap()
+            .parse()
+            .map(|shell| vec![shell])
+    }
+    .map_err(|e| anyhow!("{}", e))
+    .context(Kind::Unknown)?;
+
+    match matches.value_of("out-dir") {
+        Some(dir) => {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("I couldn't create the directory {}", dir))
+                .context(Kind::Unknown)?;
+            for shell in shells {
+                super::build().gen_completions("elm-json", shell, dir);
+            }
+        }
+        None => {
+            for shell in shells {
+                super::build().gen_completions_to("elm-json", shell, &mut io::stdout());
+            }
+        }
+    }
     Ok(())
 }
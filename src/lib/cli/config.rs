@@ -0,0 +1,116 @@
+use super::{util::MAX_INDENT, Kind};
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::{fs, io, path::PathBuf, sync::OnceLock};
+
+/// Name of the optional, project-local file holding defaults for command line flags.
+pub const FILE_NAME: &str = "elm-json.toml";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Defaults read from `elm-json.toml` in the current directory. Anything passed on the command
+/// line takes precedence.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    pub offline: Option<bool>,
+    pub registry_file: Option<PathBuf>,
+    pub color: Option<Color>,
+    pub indent: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+impl Config {
+    /// Read `elm-json.toml` from the current directory. A missing file is the same as an empty
+    /// one.
+    pub fn load() -> Result<Self> {
+        let contents = match fs::read_to_string(FILE_NAME) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).context(Kind::InvalidArgument),
+        };
+        let config: Self = toml::from_str(&contents)
+            .map_err(|e| anyhow!("I couldn't read {}: {}", FILE_NAME, e))
+            .context(Kind::InvalidArgument)?;
+        config.check().context(Kind::InvalidArgument)?;
+        Ok(config)
+    }
+
+    /// Hold settings to the same limits as the flags they stand in for.
+    fn check(&self) -> Result<()> {
+        match self.indent {
+            Some(n) if n > MAX_INDENT => Err(anyhow!(
+                "The indent in {} can be at most {} spaces, not {}.",
+                FILE_NAME,
+                MAX_INDENT,
+                n
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Make this the configuration returned by `get`. Only the first call has any effect.
+    pub fn install(self) {
+        if let Some(color) = self.color {
+            match color {
+                Color::Always => colored::control::set_override(true),
+                Color::Never => colored::control::set_override(false),
+                Color::Auto => (),
+            }
+        }
+        let _ = CONFIG.set(self);
+    }
+
+    /// The indentation to write elm.json files with, if configured.
+    pub fn indent(&self) -> Option<String> {
+        self.indent.map(|n| " ".repeat(n))
+    }
+}
+
+/// The installed configuration, or an empty one when none was installed.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_all_settings() {
+        let config: Config = toml::from_str(
+            r#"
+offline = true
+registry-file = "registry.json"
+color = "never"
+indent = 2
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.offline, Some(true));
+        assert_eq!(config.registry_file, Some(PathBuf::from("registry.json")));
+        assert!(matches!(config.color, Some(Color::Never)));
+        assert_eq!(config.indent(), Some("  ".to_string()));
+    }
+
+    #[test]
+    fn rejects_huge_indent() {
+        let config: Config = toml::from_str("indent = 100000000").unwrap();
+        assert!(config.check().is_err());
+        let config: Config = toml::from_str("indent = 16").unwrap();
+        assert!(config.check().is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_settings() {
+        assert!(toml::from_str::<Config>("ofline = true").is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use super::{error::NetworkContext, new, util, Kind};
+use crate::{
+    package::{
+        self,
+        retriever::{Options, Retriever},
+    },
+    project::{self, AppDependencies, Application, Package, Project},
+};
+use anyhow::{anyhow, Context, Result};
+use clap::ArgMatches;
+use colored::Colorize;
+use std::collections::BTreeMap;
+
+pub fn run(matches: &ArgMatches, options: &Options) -> Result<()> {
+    util::with_elm_json(matches, options, to_package, to_application)
+}
+
+fn to_package(matches: &ArgMatches, _options: &Options, info: Application) -> Result<()> {
+    if matches.value_of("to") == Some("application") {
+        println!("This elm.json is already an application.");
+        return Ok(());
+    }
+
+    let name = match matches.value_of("name") {
+        Some(name) => name.parse().context(Kind::InvalidArgument)?,
+        None => new::prompt_name()?,
+    };
+    let summary = match matches.value_of("summary") {
+        Some(summary) => {
+            package::validate_summary(summary).context(Kind::InvalidArgument)?;
+            summary.to_string()
+        }
+        None => new::prompt_summary()?,
+    };
+    let license = match matches.value_of("license") {
+        Some(license) if package::is_approved_license(license) => license.to_string(),
+        Some(license) => {
+            return Err(anyhow!("{} is not a valid SPDX license.", license))
+                .context(Kind::InvalidArgument)
+        }
+        None => new::prompt_license()?,
+    };
+
+    // Every exact version becomes a range up to the next major version, which is what `elm
+    // install` would have written for a package. Indirect dependencies have no place in a package.
+    let ranges = |deps: &AppDependencies| -> BTreeMap<package::Name, package::Range> {
+        deps.direct
+            .iter()
+            .map(|(name, &version)| (name.clone(), package::Range::from(version)))
+            .collect()
+    };
+    let mut dependencies = ranges(&info.dependencies);
+    let mut test_dependencies = ranges(&info.test_dependencies);
+
+    // Elm won't build a package that doesn't depend on elm/core, even though an application may
+    // only have it as an indirect dependency, or as a test-dependency.
+    let core = package::Name::new("elm", "core").unwrap();
+    test_dependencies.remove(&core);
+    if let Some(&version) = [&info.dependencies, &info.test_dependencies]
+        .iter()
+        .find_map(|deps| deps.direct.get(&core).or_else(|| deps.indirect.get(&core)))
+    {
+        dependencies
+            .entry(core)
+            .or_insert_with(|| package::Range::from(version));
+    }
+
+    let pkg = Package::new(name, summary, license).with_deps(dependencies, test_dependencies);
+
+    println!(
+        "I'll turn this application into the package {}, depending on:",
+        pkg.name().to_string().green()
+    );
+    for (name, range) in pkg.dependencies.iter().chain(&pkg.test_dependencies) {
+        println!("    {} {}", name, range);
+    }
+
+    save(&Project::Package(pkg), matches)
+}
+
+fn to_application(matches: &ArgMatches, options: &Options, info: Package) -> Result<()> {
+    if matches.value_of("to") == Some("package") {
+        println!("This elm.json is already a package.");
+        return Ok(());
+    }
+
+    let app = Application::new();
+    let mut retriever: Retriever =
+        Retriever::new(&app.elm_version().into(), options).network_context(Kind::Unknown)?;
+    retriever.add_deps(&info.all_dependencies().context(Kind::InvalidElmJson)?);
+
+    let res = util::resolve(options, &mut retriever)?;
+
+    let direct_names: Vec<_> = info.dependencies.keys().cloned().collect();
+    let (dependencies, test_dependencies) = project::reconstruct(&direct_names, &res);
+
+    println!(
+        "I'll turn the package {} into an application, depending on:",
+        info.name().to_string().red()
+    );
+    for (name, version) in dependencies
+        .direct
+        .iter()
+        .chain(&dependencies.indirect)
+        .chain(&test_dependencies.direct)
+        .chain(&test_dependencies.indirect)
+    {
+        println!("    {} {}", name, version);
+    }
+
+    save(
+        &Project::Application(app.with(dependencies, test_dependencies)),
+        matches,
+    )
+}
+
+fn save(project: &Project, matches: &ArgMatches) -> Result<()> {
+    if util::confirm("Should I make these changes?", matches)? {
+        util::write_elm_json(project, matches)?;
+        println!("Saved updated elm.json!");
+    } else {
+        println!("Aborting!");
+    }
+    Ok(())
+}
@@ -1,3 +1,6 @@
+use crate::{interrupt, solver};
+use anyhow::Result;
+use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Error)]
@@ -10,12 +13,73 @@ pub enum Kind {
     UnwritableElmJson,
     #[error("No valid package version")]
     NoResolution,
+    #[error("elm.json is out of date")]
+    OutOfDate,
+    #[error("Invalid argument")]
+    InvalidArgument,
     #[error("Not supported")]
     NotSupported,
+    #[error("Could not reach package.elm-lang.org")]
+    NetworkError,
+    #[error("Took too long to find a solution")]
+    Timeout,
+    #[error("Interrupted")]
+    Interrupted,
+    /// Answering "no" to `elm-json available`, which has nothing further to report.
+    #[error("Not available")]
+    Unavailable,
     #[error("Unknown error")]
     Unknown,
 }
 
+impl Kind {
+    /// The kind attached to an error, or `Kind::Unknown` when none was attached.
+    pub fn of(e: &anyhow::Error) -> Self {
+        e.downcast_ref::<Self>().copied().unwrap_or(Self::Unknown)
+    }
+}
+
+/// Machine readable representation of an error, used with `--json`.
+#[derive(Serialize, Debug)]
+pub struct Report {
+    error: String,
+    kind: String,
+    detail: Option<String>,
+}
+
+impl From<&anyhow::Error> for Report {
+    fn from(e: &anyhow::Error) -> Self {
+        let kind = Kind::of(e);
+        Self {
+            error: kind.to_string().to_lowercase(),
+            kind: format!("{:?}", kind),
+            detail: e.source().map(|source| source.to_string()),
+        }
+    }
+}
+
+pub trait NetworkContext<T> {
+    /// Attach `kind` to the error, or `Kind::NetworkError` when it was caused by failing to talk
+    /// to the package server.
+    fn network_context(self, kind: Kind) -> Result<T>;
+}
+
+impl<T> NetworkContext<T> for Result<T> {
+    fn network_context(self, kind: Kind) -> Result<T> {
+        self.map_err(|e| {
+            if e.chain().any(|cause| cause.is::<isahc::Error>()) {
+                e.context(Kind::NetworkError)
+            } else if e.is::<solver::Timeout>() {
+                e.context(Kind::Timeout)
+            } else if e.is::<interrupt::Interrupted>() {
+                e.context(Kind::Interrupted)
+            } else {
+                e.context(kind)
+            }
+        })
+    }
+}
+
 // impl Fail for Error {
 //     fn name(&self) -> Option<&str> {
 //         self.0.name()
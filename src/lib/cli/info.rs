@@ -0,0 +1,111 @@
+use super::util;
+use crate::{
+    package::retriever::Options,
+    project::{Application, Package},
+};
+use anyhow::Result;
+use clap::ArgMatches;
+use serde_json::Value;
+
+pub fn run(matches: &ArgMatches, options: &Options) -> Result<()> {
+    util::with_elm_json(matches, options, info_application, info_package)
+}
+
+fn info_application(matches: &ArgMatches, _options: &Options, info: Application) -> Result<()> {
+    show(
+        matches,
+        vec![
+            ("type", "Type", "application".into()),
+            (
+                "elm-version",
+                "Elm version",
+                info.elm_version().to_string().into(),
+            ),
+            (
+                "direct-dependencies",
+                "Direct dependencies",
+                info.dependencies.direct.len().into(),
+            ),
+            (
+                "indirect-dependencies",
+                "Indirect dependencies",
+                info.dependencies.indirect.len().into(),
+            ),
+            (
+                "direct-test-dependencies",
+                "Direct test-dependencies",
+                info.test_dependencies.direct.len().into(),
+            ),
+            (
+                "indirect-test-dependencies",
+                "Indirect test-dependencies",
+                info.test_dependencies.indirect.len().into(),
+            ),
+        ],
+    );
+    Ok(())
+}
+
+fn info_package(matches: &ArgMatches, _options: &Options, info: Package) -> Result<()> {
+    show(
+        matches,
+        vec![
+            ("type", "Type", "package".into()),
+            ("name", "Name", info.name().to_string().into()),
+            ("summary", "Summary", info.summary().into()),
+            ("license", "License", info.license().into()),
+            ("version", "Version", info.version().to_string().into()),
+            (
+                "exposed-modules",
+                "Exposed modules",
+                info.exposed_modules().modules().len().into(),
+            ),
+            (
+                "elm-version",
+                "Elm version",
+                info.elm_version().to_string().into(),
+            ),
+            (
+                "dependencies",
+                "Dependencies",
+                info.dependencies.len().into(),
+            ),
+            (
+                "test-dependencies",
+                "Test-dependencies",
+                info.test_dependencies.len().into(),
+            ),
+        ],
+    );
+    Ok(())
+}
+
+/// Print the fields as aligned `Label: value` lines, or as a JSON object with `--json`.
+fn show(matches: &ArgMatches, fields: Vec<(&str, &str, Value)>) {
+    if matches.is_present("json") {
+        let fields: serde_json::Map<_, _> = fields
+            .into_iter()
+            .map(|(key, _, value)| (key.to_string(), value))
+            .collect();
+        println!("{}", Value::Object(fields));
+        return;
+    }
+
+    let width = fields
+        .iter()
+        .map(|(_, label, _)| label.len())
+        .max()
+        .unwrap_or(0);
+    for (_, label, value) in fields {
+        let value = match value {
+            Value::String(s) => s,
+            other => other.to_string(),
+        };
+        println!(
+            "{:width$}  {}",
+            format!("{}:", label),
+            value,
+            width = width + 1
+        );
+    }
+}
@@ -1,47 +1,76 @@
-use super::{util, Kind};
+use super::{error::NetworkContext, util, Kind};
 use crate::{
     diff,
     package::{
         self,
-        retriever::{PackageId, Retriever},
+        retriever::{Options, PackageId, Retriever},
     },
-    project::{self, Application, Package, Project},
-    semver,
-    solver::Resolver,
+    project::{self, AppDependencies, Application, Package, Project},
+    semver, solver,
 };
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use clap::ArgMatches;
 use colored::Colorize;
-use petgraph::{self, visit::IntoNodeReferences};
-use std::collections::{btree_map::Keys, BTreeMap};
+use std::collections::{btree_map::Keys, BTreeMap, HashSet};
+use std::time::Instant;
 
-pub fn run(matches: &ArgMatches, offline: bool) -> Result<()> {
-    util::with_elm_json(matches, offline, install_application, install_package)
+pub fn run(matches: &ArgMatches, options: &Options) -> Result<()> {
+    util::with_elm_json(matches, options, install_application, install_package)
 }
 
-fn install_package(matches: &ArgMatches, offline: bool, info: Package) -> Result<()> {
-    let mut retriever =
-        Retriever::new(&info.elm_version().to_constraint(), offline).context(Kind::Unknown)?;
+fn install_package(matches: &ArgMatches, options: &Options, info: Package) -> Result<()> {
+    if matches.is_present("indirect") {
+        return Err(anyhow!(
+            "Packages depend on ranges of versions and don't list indirect dependencies, so --indirect only works for applications."
+        ))
+        .context(Kind::NotSupported);
+    }
+
+    if matches.is_present("from-package") {
+        return Err(anyhow!(
+            "--from-package picks versions within the ranges of another package, which only makes sense for applications."
+        ))
+        .context(Kind::NotSupported);
+    }
+
+    let mut retriever = Retriever::new(&info.elm_version().to_constraint(), options)
+        .network_context(Kind::Unknown)?;
 
     let deps = info.all_dependencies().context(Kind::InvalidElmJson)?;
     retriever.add_deps(&deps);
-    let extras = util::add_extra_deps(matches, &mut retriever);
+    let extras = util::add_extra_deps(matches, &mut retriever)?;
+    check_dependency_kind(
+        &extras,
+        matches.is_present("test"),
+        &info.dependencies,
+        &info.test_dependencies,
+    )?;
 
-    let res = Resolver::new(&mut retriever)
-        .solve()
-        .context(Kind::NoResolution)?;
+    let started = Instant::now();
+    let res = util::resolve(options, &mut retriever)?;
+    util::report_timing(started, &retriever);
+    util::check_licenses(matches, Some(info.license()), &retriever, &res);
 
     let mut deps: BTreeMap<_, package::Range> = BTreeMap::new();
     let mut test_deps: BTreeMap<_, package::Range> = BTreeMap::new();
     let direct_dep_names: &mut Keys<_, _> = &mut info.dependencies.keys();
-    let root = res.node_references().next().unwrap().0;
     let for_test = matches.is_present("test");
+    let exact = matches.is_present("exact");
+    let mut picked = Vec::new();
 
-    for idx in res.neighbors(root) {
+    for idx in solver::root(&res)
+        .into_iter()
+        .flat_map(|root| res.neighbors(root))
+    {
         let item = res[idx].clone();
         if let PackageId::Pkg(dep) = item.id {
             if extras.contains(&dep) {
-                let r: package::Range = item.version.into();
+                let r = if exact {
+                    package::Range::exact(item.version)
+                } else {
+                    item.version.into()
+                };
+                picked.push((dep.clone(), item.version, Some(r)));
                 if for_test {
                     test_deps.insert(dep.clone(), r);
                 } else {
@@ -68,6 +97,17 @@ fn install_package(matches: &ArgMatches, offline: bool, info: Package) -> Result
 
     diff::show(diff::Kind::Regular, &info.dependencies, &deps);
     diff::show(diff::Kind::Test, &info.test_dependencies, &test_deps);
+    picked.sort_by(|a, b| a.0.cmp(&b.0));
+    show_picked(&picked);
+
+    if info.exposed_modules().is_empty() {
+        println!(
+            "{} this package doesn't expose any modules yet, so it can't be published.\n",
+            "Warning:".yellow()
+        );
+    }
+
+    util::check_frozen(matches)?;
 
     let updated = Project::Package(info.with_deps(deps, test_deps));
 
@@ -81,14 +121,32 @@ fn install_package(matches: &ArgMatches, offline: bool, info: Package) -> Result
     Ok(())
 }
 
-fn install_application(matches: &ArgMatches, offline: bool, info: Application) -> Result<()> {
+fn install_application(matches: &ArgMatches, options: &Options, info: Application) -> Result<()> {
     let strictness = semver::Strictness::Exact;
     let elm_version = info.elm_version();
 
     let mut retriever: Retriever =
-        Retriever::new(&elm_version.into(), offline).context(Kind::Unknown)?;
+        Retriever::new(&elm_version.into(), options).network_context(Kind::Unknown)?;
 
-    let extras = util::add_extra_deps(matches, &mut retriever);
+    let mut extras = util::add_extra_deps(matches, &mut retriever)?;
+    let from_package = add_package_deps(matches, &mut retriever)?;
+    // Dependencies we already have are kept at their current version whenever that fits.
+    retriever.add_hinted_versions(
+        info.dependencies
+            .direct
+            .iter()
+            .chain(&info.test_dependencies.direct)
+            .filter(|(k, _)| from_package.contains(*k))
+            .map(|(k, v)| (k.clone().into(), *v)),
+    );
+    extras.extend(from_package);
+    check_dependency_kind(
+        &extras,
+        matches.is_present("test"),
+        &info.dependencies.direct,
+        &info.test_dependencies.direct,
+    )?;
+    let pinned = pinned_indirect(matches, &info, &extras)?;
 
     retriever.add_preferred_versions(
         info.dependencies
@@ -106,6 +164,8 @@ fn install_application(matches: &ArgMatches, offline: bool, info: Application) -
             .map(|(k, v)| (k.clone().into(), *v)),
     );
 
+    retriever.add_preferred_versions(pinned.iter().map(|(k, v)| (k.clone().into(), *v)));
+
     retriever.add_deps(
         info.dependencies(&strictness)
             .iter()
@@ -118,9 +178,10 @@ fn install_application(matches: &ArgMatches, offline: bool, info: Application) -
             .filter(|(k, _)| !extras.contains(k)),
     );
 
-    let res = Resolver::new(&mut retriever)
-        .solve()
-        .context(Kind::NoResolution)?;
+    let started = Instant::now();
+    let res = util::resolve(options, &mut retriever)?;
+    util::report_timing(started, &retriever);
+    util::check_licenses(matches, None, &retriever, &res);
 
     let extra_direct: Vec<_> = if matches.is_present("test") {
         Vec::new()
@@ -139,6 +200,43 @@ fn install_application(matches: &ArgMatches, offline: bool, info: Application) -
 
     let deps = project::reconstruct(&orig_direct, &res);
 
+    let solved = project::flatten(&res);
+    if let Some(name) = pinned.keys().find(|name| !solved.contains_key(*name)) {
+        return Err(anyhow!(
+            "None of your dependencies depend on {}, so there's no indirect dependency to pin.",
+            name
+        ))
+        .context(Kind::InvalidArgument);
+    }
+
+    if matches.is_present("frozen")
+        && deps.0 == info.dependencies
+        && deps.1 == info.test_dependencies
+    {
+        println!("\n{}\n", util::format_header("NO CHANGES REQUIRED").green());
+        println!("All the requested packages are already available!");
+        return Ok(());
+    }
+
+    if matches.is_present("strict") {
+        let requested: HashSet<_> = extras.iter().chain(pinned.keys()).cloned().collect();
+        let changed = changed_packages(&info, &deps, &requested);
+        if !changed.is_empty() {
+            let changes: Vec<_> = changed
+                .iter()
+                .map(|(name, old, new)| match new {
+                    Some(new) => format!("- {} {} -> {}", name, old, new),
+                    None => format!("- {} {} would be removed", name, old),
+                })
+                .collect();
+            return Err(anyhow!(
+                "Installing these packages would also change the following dependencies, which isn't allowed with --strict:\n\n{}",
+                changes.join("\n")
+            ))
+            .context(Kind::NoResolution);
+        }
+    }
+
     println!(
         "\n{}\n",
         util::format_header("PACKAGE CHANGES READY").green()
@@ -165,7 +263,21 @@ fn install_application(matches: &ArgMatches, offline: bool, info: Application) -
         &deps.1.indirect,
     );
 
-    let updated = Project::Application(info.with(deps.0, deps.1));
+    let picked: Vec<_> = project::flatten(&res)
+        .into_iter()
+        .filter(|(name, _)| extras.contains(name))
+        .map(|(name, version)| (name, version, None))
+        .collect();
+    show_picked(&picked);
+
+    util::check_frozen(matches)?;
+
+    let mut updated = info.with(deps.0, deps.1);
+    if matches.is_present("freeze") {
+        updated = updated.frozen();
+    }
+
+    let updated = Project::Application(updated);
     if util::confirm("Should I make these changes?", matches)? {
         util::write_elm_json(&updated, matches)?;
         println!("Saved updated elm.json!");
@@ -175,3 +287,146 @@ fn install_application(matches: &ArgMatches, offline: bool, info: Application) -
 
     Ok(())
 }
+
+/// With `--from-package`, the dependencies of that package are installed too, constrained to the
+/// ranges it allows.
+fn add_package_deps(
+    matches: &ArgMatches,
+    retriever: &mut Retriever,
+) -> Result<HashSet<package::Name>> {
+    let path = match matches.value_of("from-package") {
+        Some(path) => path,
+        None => return Ok(HashSet::new()),
+    };
+
+    let source = match util::read_elm_json_at(path, matches)? {
+        Project::Package(source) => source,
+        Project::Application(_) => {
+            return Err(anyhow!(
+                "{} is the elm.json of an application, but --from-package needs a package to take version ranges from.",
+                path
+            ))
+            .context(Kind::InvalidArgument)
+        }
+    };
+
+    let deps = source.dependencies();
+    for (name, range) in &deps {
+        retriever.add_dep(name.clone(), Some(range.clone().into()));
+    }
+    Ok(deps.into_iter().map(|(name, _)| name).collect())
+}
+
+/// A package can't be both a dependency and a test-dependency, so refuse to install a dependency as
+/// a test-dependency or the other way around, rather than quietly moving it.
+fn check_dependency_kind<V>(
+    extras: &HashSet<package::Name>,
+    for_test: bool,
+    deps: &BTreeMap<package::Name, V>,
+    test_deps: &BTreeMap<package::Name, V>,
+) -> Result<()> {
+    let (existing, kind, requested) = if for_test {
+        (deps, "dependency", "test-dependency")
+    } else {
+        (test_deps, "test-dependency", "dependency")
+    };
+
+    let mut clashes: Vec<_> = extras
+        .iter()
+        .filter(|name| existing.contains_key(name))
+        .collect();
+    clashes.sort();
+
+    match clashes.as_slice() {
+        [] => Ok(()),
+        [name] => Err(anyhow!(
+            "{} is already a {}, so it can't also be installed as a {}. Uninstall it first if you want to move it.",
+            name, kind, requested
+        ))
+        .context(Kind::InvalidArgument),
+        _ => Err(anyhow!(
+            "{} are already {}s, so they can't also be installed as {}s. Uninstall them first if you want to move them.",
+            clashes.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "),
+            kind,
+            requested
+        ))
+        .context(Kind::InvalidArgument),
+    }
+}
+
+fn show_picked(picked: &[(package::Name, semver::Version, Option<package::Range>)]) {
+    if picked.is_empty() {
+        return;
+    }
+
+    println!("I picked these versions for the requested packages:\n");
+    for (name, version, range) in picked {
+        match range {
+            Some(range) => println!(
+                "- {} {}, added as {}",
+                name,
+                version.to_string().green(),
+                range
+            ),
+            None => println!("- {} {}", name, version.to_string().green()),
+        }
+    }
+    println!();
+}
+
+/// The indirect dependencies to pin with `--indirect`, which can't also be direct dependencies.
+fn pinned_indirect(
+    matches: &ArgMatches,
+    info: &Application,
+    extras: &HashSet<package::Name>,
+) -> Result<BTreeMap<package::Name, semver::Version>> {
+    let mut pinned = BTreeMap::new();
+    for dep in matches.values_of("indirect").into_iter().flatten() {
+        let (name, version) = util::parse_exact_package(dep)
+            .with_context(|| format!("I couldn't make sense of the package {}", dep))
+            .context(Kind::InvalidArgument)?;
+
+        if extras.contains(&name)
+            || info.dependencies.direct.contains_key(&name)
+            || info.test_dependencies.direct.contains_key(&name)
+        {
+            return Err(anyhow!(
+                "{} is a direct dependency, so it can't be pinned as an indirect one.",
+                name
+            ))
+            .context(Kind::InvalidArgument);
+        }
+        pinned.insert(name, version);
+    }
+    Ok(pinned)
+}
+
+/// Packages in the original elm.json, other than the ones being installed, whose version would
+/// change or which would be dropped altogether.
+fn changed_packages(
+    info: &Application,
+    deps: &(AppDependencies, AppDependencies),
+    extras: &HashSet<package::Name>,
+) -> Vec<(package::Name, semver::Version, Option<semver::Version>)> {
+    let all = |d: &AppDependencies, t: &AppDependencies| -> BTreeMap<_, _> {
+        d.direct
+            .iter()
+            .chain(d.indirect.iter())
+            .chain(t.direct.iter())
+            .chain(t.indirect.iter())
+            .map(|(k, v)| (k.clone(), *v))
+            .collect()
+    };
+
+    let before = all(&info.dependencies, &info.test_dependencies);
+    let after = all(&deps.0, &deps.1);
+
+    before
+        .into_iter()
+        .filter(|(name, _)| !extras.contains(name))
+        .filter_map(|(name, old)| match after.get(&name) {
+            Some(new) if *new == old => None,
+            new => Some((name, old, new.copied())),
+        })
+        .collect()
+}
@@ -1,17 +1,55 @@
 use clap::{App, AppSettings, Arg, SubCommand};
 
+pub mod available;
+pub mod bump;
 pub mod completions;
+pub mod config;
+pub mod convert;
 pub mod error;
+pub mod info;
 pub mod install;
 pub mod new;
+pub mod rename;
+pub mod search;
 pub mod solve;
 pub mod tree;
 pub mod uninstall;
 pub mod upgrade;
 pub mod util;
+pub mod validate;
 
 pub use error::Kind;
 
+/// Whether we're running somewhere nobody can answer prompts, like a CI pipeline. Most CI systems
+/// set `CI`, and `ELM_JSON_NONINTERACTIVE` can be used to force this either way.
+pub fn noninteractive() -> bool {
+    fn enabled(var: &str) -> Option<bool> {
+        std::env::var(var)
+            .ok()
+            .map(|value| !matches!(value.to_lowercase().as_str(), "" | "0" | "false"))
+    }
+
+    enabled("ELM_JSON_NONINTERACTIVE")
+        .or_else(|| enabled("CI"))
+        .unwrap_or(false)
+}
+
+/// For the subcommands writing elm.json.
+fn indent_arg() -> Arg<'static, 'static> {
+    Arg::with_name("indent")
+        .help("Number of spaces to indent the written elm.json with, defaults to the indentation of the existing file")
+        .long("indent")
+        .takes_value(true)
+        .value_name("N")
+        .validator(util::valid_indent)
+}
+
+fn sort_ignore_case_arg() -> Arg<'static, 'static> {
+    Arg::with_name("sort-ignore-case")
+        .help("Sort dependencies in the written elm.json without regard to case")
+        .long("sort-ignore-case")
+}
+
 pub fn build() -> App<'static, 'static> {
     App::new("elm-json")
         .version(env!("CARGO_PKG_VERSION"))
@@ -25,12 +63,94 @@ pub fn build() -> App<'static, 'static> {
                 .multiple(true)
                 .help("Sets the level of verbosity"),
         )
+        .arg(
+            Arg::with_name("log-format")
+                .long("log-format")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(&["text", "json"])
+                .default_value("text")
+                .help("Format of the log lines enabled by -v"),
+        )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .global(true)
+                .help("Report errors, and output where possible, as JSON"),
+        )
+        .arg(
+            Arg::with_name("explain")
+                .long("explain")
+                .global(true)
+                .conflicts_with("json")
+                .help("When no valid set of package versions can be found, walk through the conflict step by step"),
+        )
+        .arg(
+            Arg::with_name("allow-comments")
+                .long("allow-comments")
+                .global(true)
+                .help("Ignore comments in elm.json, which are lost when it is written"),
+        )
         .arg(
             Arg::with_name("offline")
                 .long("offline")
                 .multiple(false)
                 .help("Enable offline mode, which means no HTTP traffic will happen"),
         )
+        .arg(
+            Arg::with_name("online")
+                .long("online")
+                .conflicts_with_all(&["offline", "locked"])
+                .help("Allow HTTP traffic, even when elm-json.toml sets offline = true"),
+        )
+        .arg(
+            Arg::with_name("locked")
+                .long("locked")
+                .help("Like --offline, but fail when anything needed isn't cached yet"),
+        )
+        .arg(
+            Arg::with_name("no-progress")
+                .long("no-progress")
+                .help("Don't show a spinner while downloading package information"),
+        )
+        .arg(
+            Arg::with_name("prefer-cached")
+                .long("prefer-cached")
+                .help("Prefer package versions that are already cached locally, to avoid downloads"),
+        )
+        .arg(
+            Arg::with_name("memoize")
+                .long("memoize")
+                .help("Remember the solution for an unchanged elm.json, so tree and solve can skip solving it next time"),
+        )
+        .arg(
+            Arg::with_name("refresh")
+                .long("refresh")
+                .help("Fetch the list of available package versions from scratch instead of only what's new"),
+        )
+        .arg(
+            Arg::with_name("max-age")
+                .long("max-age")
+                .takes_value(true)
+                .value_name("DURATION")
+                .validator(util::valid_duration)
+                .help("Fetch the list of available package versions from scratch when the cached one is older than this, e.g. 12h or 7d"),
+        )
+        .arg(
+            Arg::with_name("solve-timeout")
+                .long("solve-timeout")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .validator(util::valid_count)
+                .help("Give up on finding a solution after this many seconds"),
+        )
+        .arg(
+            Arg::with_name("registry-file")
+                .long("registry-file")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Read the list of available package versions from a local file rather than from package.elm-lang.org or the cache"),
+        )
         .subcommand(
             SubCommand::with_name("upgrade")
                 .about("Bring your dependencies up to date")
@@ -39,10 +159,27 @@ pub fn build() -> App<'static, 'static> {
                         .help("Allow major versions bumps")
                         .long("unsafe"),
                 )
+                .arg(
+                    Arg::with_name("ignore-test")
+                        .help("Leave test-dependencies at their current versions")
+                        .long("ignore-test"),
+                )
+                .arg(
+                    Arg::with_name("frozen")
+                        .help("Fail, without writing anything, if elm.json would change")
+                        .long("frozen"),
+                )
+                .arg(
+                    Arg::with_name("check")
+                        .help("Only list the available upgrades, failing if there are any")
+                        .long("check"),
+                )
                 .arg(Arg::with_name("yes")
                      .help("Answer \"yes\" to all questions")
                      .long("yes")
                 )
+                .arg(indent_arg())
+                .arg(sort_ignore_case_arg())
                 .arg(
                     Arg::with_name("INPUT")
                         .help("The elm.json file to upgrade")
@@ -57,17 +194,71 @@ pub fn build() -> App<'static, 'static> {
                         .help("Install as a test-dependency")
                         .long("test"),
                 )
+                .arg(
+                    Arg::with_name("strict")
+                        .help("For applications, fail rather than change the version of any other dependency")
+                        .long("strict"),
+                )
+                .arg(
+                    Arg::with_name("freeze")
+                        .help("For applications, mark the solved indirect dependencies as authoritative so upgrade warns before changing them")
+                        .long("freeze"),
+                )
+                .arg(
+                    Arg::with_name("exact")
+                        .help("For packages, only allow the exact installed version rather than the next major")
+                        .long("exact"),
+                )
+                .arg(
+                    Arg::with_name("check-licenses")
+                        .help("Warn about dependencies with a copyleft license")
+                        .long("check-licenses"),
+                )
+                .arg(
+                    Arg::with_name("allow-license")
+                        .help("License to accept when checking licenses, may be repeated")
+                        .long("allow-license")
+                        .takes_value(true)
+                        .value_name("LICENSE")
+                        .multiple(true)
+                        .number_of_values(1)
+                        .requires("check-licenses"),
+                )
+                .arg(
+                    Arg::with_name("frozen")
+                        .help("Fail, without writing anything, if elm.json would change")
+                        .long("frozen"),
+                )
                 .arg(Arg::with_name("yes")
                      .help("Answer \"yes\" to all questions")
                      .long("yes")
                 )
+                .arg(indent_arg())
+                .arg(sort_ignore_case_arg())
+                .arg(
+                    Arg::with_name("indirect")
+                        .help("For applications, pin an indirect dependency to an exact version, e.g. elm/json@1.1.2")
+                        .long("indirect")
+                        .takes_value(true)
+                        .value_name("PACKAGE@VERSION")
+                        .validator(util::valid_exact_package)
+                        .multiple(true)
+                        .number_of_values(1),
+                )
+                .arg(
+                    Arg::with_name("from-package")
+                        .help("For applications, also install the dependencies of this package's elm.json, at versions within its ranges")
+                        .long("from-package")
+                        .takes_value(true)
+                        .value_name("FILE"),
+                )
                 .arg(
                     Arg::with_name("extra")
-                        .help("Package to install, e.g. elm/core or elm/core@1.0.2 or elm/core@1")
+                        .help("Package to install, e.g. elm/core or elm/core@1.0.2 or elm/core@1, or just core if only one author has a package by that name")
                         .takes_value(true)
                         .value_name("PACKAGE")
-                        .validator(util::valid_package)
-                        .required(true)
+                        .validator(util::valid_package_shorthand)
+                        .required_unless_one(&["indirect", "from-package"])
                         .multiple(true),
                 )
                 .arg(
@@ -84,6 +275,21 @@ pub fn build() -> App<'static, 'static> {
                      .help("Answer \"yes\" to all questions")
                      .long("yes")
                 )
+                .arg(indent_arg())
+                .arg(sort_ignore_case_arg())
+                .arg(
+                    Arg::with_name("max-removals")
+                        .help("Ask again, even with --yes, when more than N packages would be removed from elm.json")
+                        .long("max-removals")
+                        .takes_value(true)
+                        .value_name("N")
+                        .validator(util::valid_count),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .help("Remove packages without asking again, even when that's more than --max-removals allows")
+                        .long("force"),
+                )
                 .arg(
                     Arg::with_name("extra")
                         .help("Package to uninstall, e.g. elm/html")
@@ -100,20 +306,154 @@ pub fn build() -> App<'static, 'static> {
                         .default_value("elm.json"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("bump")
+                .about("Bump the version of a package")
+                .arg(
+                    Arg::with_name("PART")
+                        .help("Which part of the version to increment")
+                        .required(true)
+                        .possible_values(&["major", "minor", "patch"]),
+                )
+                .arg(Arg::with_name("yes")
+                     .help("Answer \"yes\" to all questions")
+                     .long("yes")
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("The elm.json file to update")
+                        .last(true)
+                        .default_value("elm.json"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("info")
+                .about("Summarize an elm.json")
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("The elm.json file to summarize")
+                        .default_value("elm.json"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rename")
+                .about("Change the name of a package, e.g. when maintaining a fork")
+                .arg(
+                    Arg::with_name("NAME")
+                        .help("The new name of the package, e.g. author/project")
+                        .required(true)
+                        .validator(util::valid_package_name),
+                )
+                .arg(Arg::with_name("yes")
+                     .help("Answer \"yes\" to all questions")
+                     .long("yes")
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("The elm.json file to update")
+                        .last(true)
+                        .default_value("elm.json"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("convert")
+                .about("Turn an application into a package, or the other way around")
+                .arg(
+                    Arg::with_name("to")
+                        .help("What the elm.json should become")
+                        .long("to")
+                        .takes_value(true)
+                        .value_name("TYPE")
+                        .possible_values(&["application", "package"])
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("name")
+                        .help("Name of the new package, e.g. author/project, rather than asking for it")
+                        .long("name")
+                        .takes_value(true)
+                        .value_name("NAME")
+                        .validator(util::valid_package_name),
+                )
+                .arg(
+                    Arg::with_name("summary")
+                        .help("Summary of the new package, rather than asking for it")
+                        .long("summary")
+                        .takes_value(true)
+                        .value_name("SUMMARY"),
+                )
+                .arg(
+                    Arg::with_name("license")
+                        .help("License of the new package in SPDX format, rather than asking for it")
+                        .long("license")
+                        .takes_value(true)
+                        .value_name("LICENSE"),
+                )
+                .arg(Arg::with_name("yes")
+                     .help("Answer \"yes\" to all questions")
+                     .long("yes")
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("The elm.json file to convert")
+                        .last(true)
+                        .default_value("elm.json"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("validate")
+                .about("Check whether an elm.json is valid")
+                .arg(
+                    Arg::with_name("all")
+                        .help("Report every problem rather than only the first one")
+                        .long("all"),
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("The elm.json file to check")
+                        .last(true)
+                        .default_value("elm.json"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("tree")
                 .about("List entire dependency graph as a tree")
+                .arg(
+                    Arg::with_name("dot")
+                        .help("Output the dependency graph in Graphviz DOT format")
+                        .long("dot")
+                        .conflicts_with("ascii"),
+                )
+                .arg(
+                    Arg::with_name("ascii")
+                        .help("Draw the tree using ASCII characters only")
+                        .long("ascii"),
+                )
                 .arg(
                     Arg::with_name("test")
                         .help("Promote test-dependencies to top-level dependencies")
                         .long("test"),
                 )
+                .arg(
+                    Arg::with_name("include-elm")
+                        .help("Include the chosen Elm version in the output")
+                        .long("include-elm"),
+                )
                 .arg(
                     Arg::with_name("package")
-                        .help("Limit output to show path to some (indirect) dependency")
+                        .help("Limit output to show paths to some (indirect) dependencies, e.g. elm/http, elm/http@2.0.0, elm/* or *html*")
                         .takes_value(true)
                         .value_name("PACKAGE")
-                        .validator(util::valid_package),
+                        .validator(util::valid_package_pattern),
+                )
+                .arg(
+                    Arg::with_name("reverse")
+                        .help("Show everything that (indirectly) depends on this package instead, e.g. elm/json")
+                        .long("reverse")
+                        .takes_value(true)
+                        .value_name("PACKAGE")
+                        .validator(util::valid_package_name)
+                        .conflicts_with("package"),
                 )
                 .arg(
                     Arg::with_name("INPUT")
@@ -122,6 +462,25 @@ pub fn build() -> App<'static, 'static> {
                         .default_value("elm.json"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("available")
+                .about("Check whether a version of a package is available, for use in scripts")
+                .arg(
+                    Arg::with_name("PACKAGE")
+                        .help("The package and exact version to look for, e.g. elm/core@1.0.2")
+                        .required(true)
+                        .validator(util::valid_exact_package),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("search")
+                .about("Search the known packages by author or project name")
+                .arg(
+                    Arg::with_name("QUERY")
+                        .help("Part of the author or project name, e.g. json")
+                        .required(true),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("solve")
                 .about("Figure out a solution given the version constraints in your elm.json")
@@ -132,12 +491,65 @@ pub fn build() -> App<'static, 'static> {
                         .help("Promote test-dependencies to top-level dependencies")
                         .long("test"),
                 )
+                .arg(
+                    Arg::with_name("separate-test")
+                        .help("Output test-direct and test-indirect next to direct and indirect")
+                        .long("separate-test")
+                        .requires("test"),
+                )
+                .arg(
+                    Arg::with_name("elm-format")
+                        .help("Output elm-version, dependencies and test-dependencies exactly as they'd appear in the elm.json of an application")
+                        .long("elm-format")
+                        .requires("test")
+                        .conflicts_with_all(&["separate-test", "include-elm"]),
+                )
+                .arg(
+                    Arg::with_name("check-licenses")
+                        .help("Warn about dependencies with a copyleft license")
+                        .long("check-licenses"),
+                )
+                .arg(
+                    Arg::with_name("allow-license")
+                        .help("License to accept when checking licenses, may be repeated")
+                        .long("allow-license")
+                        .takes_value(true)
+                        .value_name("LICENSE")
+                        .multiple(true)
+                        .number_of_values(1)
+                        .requires("check-licenses"),
+                )
+                .arg(
+                    Arg::with_name("include-elm")
+                        .help("Include the chosen Elm version in the output")
+                        .long("include-elm"),
+                )
+                .arg(
+                    Arg::with_name("no-elm")
+                        .help("Ignore the Elm version of the project, so only the packages constrain it")
+                        .long("no-elm"),
+                )
+                .arg(
+                    Arg::with_name("pretty")
+                        .help("Pretty-print the output, rather than printing it on a single line")
+                        .long("pretty"),
+                )
                 .arg(
                     Arg::with_name("minimize")
                         .help("Choose lowest available versions rather than highest")
                         .short("m")
                         .long("minimize"),
                 )
+                .arg(
+                    Arg::with_name("minimize-package")
+                        .help("Choose the lowest available version for this package only, e.g. elm/json, may be repeated")
+                        .long("minimize-package")
+                        .takes_value(true)
+                        .value_name("PACKAGE")
+                        .validator(util::valid_package_name)
+                        .multiple(true)
+                        .number_of_values(1),
+                )
                 .arg(
                     Arg::with_name("extra")
                         .short("e")
@@ -148,9 +560,20 @@ pub fn build() -> App<'static, 'static> {
                         .validator(util::valid_package)
                         .multiple(true),
                 )
+                .arg(
+                    Arg::with_name("prefer")
+                        .long("prefer")
+                        .help("Try this version of a package first, e.g. elm/core@1.0.2, but pick another one if it doesn't fit")
+                        .takes_value(true)
+                        .value_name("PACKAGE@VERSION")
+                        .validator(util::valid_exact_package)
+                        .multiple(true)
+                        .number_of_values(1),
+                )
                 .arg(
                     Arg::with_name("INPUT")
-                        .help("The elm.json file to solve")
+                        .help("The elm.json file(s) to solve, solutions for multiple files are keyed by path")
+                        .multiple(true)
                         .default_value("elm.json"),
                 ),
         )
@@ -160,10 +583,36 @@ pub fn build() -> App<'static, 'static> {
                 .setting(AppSettings::Hidden)
                 .arg(
                     Arg::with_name("SHELL")
-                        .required(true)
-                        .possible_values(&["bash", "fish", "zsh"])
+                        .required_unless("all")
+                        .possible_values(&["bash", "elvish", "fish", "powershell", "zsh"])
                         .help("The shell to generate the script for")
                 )
+                .arg(
+                    Arg::with_name("all")
+                        .help("Generate scripts for every supported shell")
+                        .long("all")
+                        .conflicts_with("SHELL")
+                        .requires("out-dir"),
+                )
+                .arg(
+                    Arg::with_name("out-dir")
+                        .help("Write the script to a file in this directory rather than to stdout")
+                        .long("out-dir")
+                        .takes_value(true)
+                        .value_name("DIR"),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("new")
+                .about("Create a new elm.json file")
+                .arg(
+                    Arg::with_name("source-dir")
+                        .help("Source directory for a new application, may be repeated [default: src]")
+                        .long("source-dir")
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .multiple(true)
+                        .number_of_values(1),
+                ),
         )
-        .subcommand(SubCommand::with_name("new").about("Create a new elm.json file"))
 }
@@ -1,37 +1,66 @@
-use super::Kind;
-use crate::project::{Application, Package, Project};
-use anyhow::{anyhow, bail, Context, Error, Result};
+use super::{config, error::NetworkContext, util, Kind};
+use crate::{
+    package::{
+        self,
+        retriever::{Options, Retriever},
+    },
+    project::{self, Application, Package, Project},
+    semver,
+};
+use anyhow::{anyhow, Context, Error, Result};
 use clap::ArgMatches;
 use colored::Colorize;
 use serde::Serialize;
 use std::{fs::OpenOptions, io::BufWriter};
 
-pub fn run(matches: &ArgMatches) -> Result<()> {
-    let options = vec!["application", "package"];
+pub fn run(matches: &ArgMatches, options: &Options) -> Result<()> {
+    // Every answer comes from a prompt, so there's nothing sensible to do without a terminal.
+    if super::noninteractive() || !console::user_attended() {
+        return Err(anyhow!(
+            "Creating an elm.json asks a few questions, so I can only do that in an interactive terminal."
+        ))
+        .context(Kind::NotSupported);
+    }
+
+    let project_types = vec!["application", "package"];
     let option_idx = dialoguer::Select::new()
         .with_prompt("What type of elm.json file do you want to create?")
-        .items(&options)
+        .items(&project_types)
         .default(0)
         .interact()
         .context(Kind::Unknown)?;
 
-    match options[option_idx] {
-        "application" => create_application(matches),
+    match project_types[option_idx] {
+        "application" => create_application(matches, options),
         "package" => create_package(matches),
         _ => unreachable!(),
     }
 }
 
 fn create_package(_matches: &ArgMatches) -> Result<()> {
-    let name = until_valid(
+    let proj = Project::Package(Package::new(
+        prompt_name()?,
+        prompt_summary()?,
+        prompt_license()?,
+    ));
+    create_elm_json(&proj)
+}
+
+pub(super) fn prompt_name() -> Result<package::Name> {
+    until_valid(
         str::parse,
         "Enter a name for your package: (format: author/project)",
-    )?;
-    let summary = until_valid(
-        validate_summary,
+    )
+}
+
+pub(super) fn prompt_summary() -> Result<String> {
+    until_valid(
+        |input| package::validate_summary(input).map(|_| input.to_string()),
         "Enter a summary for your package (max 80 characters)",
-    )?;
+    )
+}
 
+pub(super) fn prompt_license() -> Result<String> {
     let license_options = vec!["BSD-3-Clause", "MIT", "other..."];
     let license_option_idx = dialoguer::Select::new()
         .with_prompt("Choose a license for your package")
@@ -40,30 +69,19 @@ fn create_package(_matches: &ArgMatches) -> Result<()> {
         .interact()
         .context(Kind::Unknown)?;
 
-    let license = match license_options[license_option_idx] {
+    match license_options[license_option_idx] {
         "other..." => until_valid(
             |input| {
-                if APPROVED_LICENSES.contains(&&*input) {
+                if package::is_approved_license(input) {
                     Ok(input.to_string())
                 } else {
                     Err(anyhow!("Please pick a valid license"))
                 }
             },
             "License in SPDX format",
-        )?,
-        selected_license => selected_license.to_string(),
-    };
-
-    let proj = Project::Package(Package::new(name, summary, license));
-    create_elm_json(&proj)
-}
-
-fn validate_summary(summary: &str) -> Result<String, Error> {
-    if summary.len() > 80 {
-        bail!("Summary may not be over 80 characters long.")
+        ),
+        selected_license => Ok(selected_license.to_string()),
     }
-
-    Ok(summary.to_string())
 }
 
 fn until_valid<X, F>(validate: F, prompt: &str) -> Result<X>
@@ -88,9 +106,52 @@ where
     Ok(res)
 }
 
-fn create_application(_matches: &ArgMatches) -> Result<()> {
-    let proj = Project::Application(Application::new());
-    create_elm_json(&proj)
+fn create_application(matches: &ArgMatches, options: &Options) -> Result<()> {
+    let app = match matches.values_of_lossy("source-dir") {
+        Some(dirs) => Application::new().with_source_directories(dirs),
+        None => Application::new(),
+    };
+
+    let selected = dialoguer::MultiSelect::new()
+        .with_prompt("Which packages do you want to start with? (space to select)")
+        .items(STARTER_PACKAGES)
+        .interact()
+        .context(Kind::Unknown)?;
+
+    let app = if selected.is_empty() {
+        app
+    } else {
+        let packages = selected
+            .into_iter()
+            .map(|idx| STARTER_PACKAGES[idx].parse())
+            .collect::<Result<Vec<package::Name>>>()
+            .context(Kind::Unknown)?;
+        let mut retriever: Retriever =
+            Retriever::new(&app.elm_version().into(), options).network_context(Kind::Unknown)?;
+        with_starter_packages(app, packages, options, &mut retriever)?
+    };
+
+    create_elm_json(&Project::Application(app))
+}
+
+/// Add the starter `packages` to `app` as direct dependencies, along with whatever they need.
+fn with_starter_packages(
+    app: Application,
+    packages: Vec<package::Name>,
+    options: &Options,
+    retriever: &mut Retriever,
+) -> Result<Application> {
+    retriever.add_deps(&app.dependencies(&semver::Strictness::Safe));
+    for name in &packages {
+        retriever.add_dep(name.clone(), None);
+    }
+
+    let res = util::resolve(options, retriever)?;
+
+    let mut direct_names: Vec<_> = app.dependencies.direct.keys().cloned().collect();
+    direct_names.extend(packages);
+    let (dependencies, test_dependencies) = project::reconstruct(&direct_names, &res);
+    Ok(app.with(dependencies, test_dependencies))
 }
 
 fn create_elm_json(info: &Project) -> Result<()> {
@@ -100,111 +161,54 @@ fn create_elm_json(info: &Project) -> Result<()> {
         .open("elm.json")
         .context(Kind::UnwritableElmJson)?;
     let writer = BufWriter::new(file);
-    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let indent = config::get().indent().unwrap_or_else(|| "    ".to_string());
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
     let mut serializer = serde_json::Serializer::with_formatter(writer, formatter);
 
     info.serialize(&mut serializer).context(Kind::Unknown)?;
     Ok(())
 }
 
-const APPROVED_LICENSES: &[&str] = &[
-    "AFL-1.1",
-    "AFL-1.2",
-    "AFL-2.0",
-    "AFL-2.1",
-    "AFL-3.0",
-    "APL-1.0",
-    "Apache-1.1",
-    "Apache-2.0",
-    "APSL-1.0",
-    "APSL-1.1",
-    "APSL-1.2",
-    "APSL-2.0",
-    "Artistic-1.0",
-    "Artistic-1.0-Perl",
-    "Artistic-1.0-cl8",
-    "Artistic-2.0",
-    "AAL",
-    "BSL-1.0",
-    "BSD-2-Clause",
-    "BSD-3-Clause",
-    "0BSD",
-    "CECILL-2.1",
-    "CNRI-Python",
-    "CDDL-1.0",
-    "CPAL-1.0",
-    "CPL-1.0",
-    "CATOSL-1.1",
-    "CUA-OPL-1.0",
-    "EPL-1.0",
-    "ECL-1.0",
-    "ECL-2.0",
-    "EFL-1.0",
-    "EFL-2.0",
-    "Entessa",
-    "EUDatagrid",
-    "EUPL-1.1",
-    "Fair",
-    "Frameworx-1.0",
-    "AGPL-3.0",
-    "GPL-2.0",
-    "GPL-3.0",
-    "LGPL-2.1",
-    "LGPL-3.0",
-    "LGPL-2.0",
-    "HPND",
-    "IPL-1.0",
-    "Intel",
-    "IPA",
-    "ISC",
-    "LPPL-1.3c",
-    "LiLiQ-P-1.1",
-    "LiLiQ-Rplus-1.1",
-    "LiLiQ-R-1.1",
-    "LPL-1.02",
-    "LPL-1.0",
-    "MS-PL",
-    "MS-RL",
-    "MirOS",
-    "MIT",
-    "Motosoto",
-    "MPL-1.0",
-    "MPL-1.1",
-    "MPL-2.0",
-    "MPL-2.0-no-copyleft-exception",
-    "Multics",
-    "NASA-1.3",
-    "Naumen",
-    "NGPL",
-    "Nokia",
-    "NPOSL-3.0",
-    "NTP",
-    "OCLC-2.0",
-    "OGTSL",
-    "OSL-1.0",
-    "OSL-2.0",
-    "OSL-2.1",
-    "OSL-3.0",
-    "OSET-PL-2.1",
-    "PHP-3.0",
-    "PostgreSQL",
-    "Python-2.0",
-    "QPL-1.0",
-    "RPSL-1.0",
-    "RPL-1.1",
-    "RPL-1.5",
-    "RSCPL",
-    "OFL-1.1",
-    "SimPL-2.0",
-    "Sleepycat",
-    "SISSL",
-    "SPL-1.0",
-    "Watcom-1.0",
-    "UPL-1.0",
-    "NCSA",
-    "VSL-1.0",
-    "W3C",
-    "Xnet",
-    "Zlib",
-    "ZPL-2.0",
+const STARTER_PACKAGES: &[&str] = &[
+    "elm/browser",
+    "elm/html",
+    "elm/json",
+    "elm/http",
+    "elm/url",
+    "elm/time",
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::AppDependencies;
+    use std::path::Path;
+
+    #[test]
+    fn test_with_starter_packages() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let options = Options {
+            offline: true,
+            registry_file: Some(fixtures.join("registry.json")),
+            ..Options::default()
+        };
+        let app = Application::new().with(AppDependencies::new(), AppDependencies::new());
+        let mut retriever = Retriever::with_elm_home(
+            &app.elm_version().into(),
+            &options,
+            fixtures.join("elm_home"),
+        )
+        .unwrap();
+        let json: package::Name = "elm/json".parse().unwrap();
+
+        let app = with_starter_packages(app, vec![json], &options, &mut retriever).unwrap();
+        let json = serde_json::to_value(Project::Application(app)).unwrap();
+        assert_eq!(
+            json["dependencies"],
+            serde_json::json!({
+                "direct": {"elm/json": "1.1.3"},
+                "indirect": {"elm/core": "1.0.5"},
+            })
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use super::{util, Kind};
+use crate::{
+    package::{self, retriever::Options},
+    project::{Package, Project},
+};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub fn run(matches: &ArgMatches, options: &Options) -> Result<()> {
+    if util::elm_json_type(matches)? == "application" {
+        return Err(anyhow!(
+            "Applications don't have a name, so only the elm.json of a package can be renamed."
+        ))
+        .context(Kind::NotSupported);
+    }
+
+    util::with_elm_json(
+        matches,
+        options,
+        |_, _, _| bail!(Kind::NotSupported),
+        rename_package,
+    )
+}
+
+fn rename_package(matches: &ArgMatches, _options: &Options, info: Package) -> Result<()> {
+    let name: package::Name = matches
+        .value_of("NAME")
+        .unwrap()
+        .parse()
+        .context(Kind::InvalidArgument)?;
+
+    if info.name() == &name {
+        println!("This package is already called {}.", name);
+        return Ok(());
+    }
+
+    println!(
+        "I'll rename the package from {} to {}.",
+        info.name().to_string().red(),
+        name.to_string().green()
+    );
+
+    let updated = Project::Package(info.with_name(name));
+    if util::confirm("Should I make these changes?", matches)? {
+        util::write_elm_json(&updated, matches)?;
+        println!("Saved updated elm.json!");
+    } else {
+        println!("Aborting!");
+    }
+
+    Ok(())
+}
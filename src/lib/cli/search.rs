@@ -0,0 +1,40 @@
+use super::{error::NetworkContext, Kind};
+use crate::{
+    package::retriever::{Options, Retriever},
+    semver::Constraint,
+};
+use anyhow::Result;
+use clap::ArgMatches;
+use colored::Colorize;
+
+pub fn run(matches: &ArgMatches, options: &Options) -> Result<()> {
+    let query = matches.value_of("QUERY").unwrap();
+    let lowercase_query = query.to_lowercase();
+    let retriever = Retriever::new(&Constraint::any(), options).network_context(Kind::Unknown)?;
+    retriever.finish_progress();
+
+    let found: Vec<_> = retriever
+        .latest_versions()
+        .into_iter()
+        .filter(|(name, _)| {
+            name.author().to_lowercase().contains(&lowercase_query)
+                || name.project().to_lowercase().contains(&lowercase_query)
+        })
+        .collect();
+
+    if matches.is_present("json") {
+        let found: serde_json::Map<_, _> = found
+            .into_iter()
+            .map(|(name, version)| (name.to_string(), version.to_string().into()))
+            .collect();
+        println!("{}", serde_json::Value::Object(found));
+    } else if found.is_empty() {
+        println!("I couldn't find any packages matching {}.", query.bold());
+    } else {
+        for (name, version) in found {
+            println!("{} {}", name, version.to_string().green());
+        }
+    }
+
+    Ok(())
+}
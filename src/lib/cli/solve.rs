@@ -1,24 +1,109 @@
-use super::{util, Kind};
+use super::{error::NetworkContext, util, Kind};
 use crate::{
-    package::{retriever::Retriever, Package},
-    project::{AppDependencies, Application},
-    semver,
-    solver::Resolver,
+    package::{
+        self,
+        retriever::{Options, PackageId, Retriever},
+        Package,
+    },
+    project::{self, AppDependencies, Application, Project},
+    semver, solver,
 };
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use clap::ArgMatches;
+use serde::{Serialize, Serializer};
+use std::{collections::BTreeMap, time::Instant};
 
-pub fn run(matches: &ArgMatches, offline: bool) -> Result<()> {
-    util::with_elm_json(matches, offline, solve_application, solve_package)
+pub fn run(matches: &ArgMatches, options: &Options) -> Result<()> {
+    let inputs: Vec<_> = matches.values_of("INPUT").unwrap().collect();
+
+    // Solving several files shares a single retriever, so the available versions and every
+    // elm.json we come across are only loaded once.
+    let mut retriever = None;
+    let mut solutions = Vec::new();
+
+    for &input in &inputs {
+        let solution = match util::read_elm_json_at(input, matches)? {
+            Project::Application(app) => solve_application(matches, options, &mut retriever, app),
+            Project::Package(pkg) => solve_package(matches, options, &mut retriever, pkg),
+        }?;
+        solutions.push((input, solution));
+    }
+
+    let output = if let [(_, solution)] = solutions.as_slice() {
+        to_json(matches, solution)
+    } else {
+        to_json(matches, &Keyed(&solutions))
+    }
+    .context(Kind::Unknown)?;
+
+    println!("{}", output);
+    Ok(())
 }
 
-fn solve_application(matches: &ArgMatches, offline: bool, info: Application) -> Result<()> {
+fn to_json<T: Serialize>(matches: &ArgMatches, value: &T) -> serde_json::Result<String> {
+    if matches.is_present("pretty") {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
+}
+
+/// Solutions for multiple files, keyed by path in the order the files were given.
+struct Keyed<'a>(&'a [(&'a str, Solution)]);
+
+impl Serialize for Keyed<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(input, solution)| (input, solution)))
+    }
+}
+
+/// With `--no-elm`, the project's own Elm version is left out, so only the packages constrain it.
+fn retriever<'a>(
+    matches: &ArgMatches,
+    shared: &'a mut Option<Retriever>,
+    elm_version: &semver::Constraint,
+    options: &Options,
+) -> Result<&'a mut Retriever> {
+    let any = semver::Constraint::any();
+    let elm_version = if matches.is_present("no-elm") {
+        &any
+    } else {
+        elm_version
+    };
+
+    let retriever = match shared {
+        Some(retriever) => {
+            retriever.reset(elm_version);
+            retriever
+        }
+        None => {
+            let retriever = Retriever::new(elm_version, options).network_context(Kind::Unknown)?;
+            shared.insert(retriever)
+        }
+    };
+
+    for name in matches.values_of("minimize-package").into_iter().flatten() {
+        let name = name
+            .parse()
+            .map_err(|e| anyhow!("{}", e))
+            .context(Kind::InvalidArgument)?;
+        retriever.minimize_package(name);
+    }
+    Ok(retriever)
+}
+
+fn solve_application(
+    matches: &ArgMatches,
+    options: &Options,
+    shared: &mut Option<Retriever>,
+    info: Application,
+) -> Result<Solution> {
     let deps = &info.dependencies(&semver::Strictness::Exact);
     let elm_version = info.elm_version();
 
-    let mut retriever: Retriever =
-        Retriever::new(&elm_version.into(), offline).context(Kind::Unknown)?;
-    let extras = util::add_extra_deps(matches, &mut retriever);
+    let retriever = retriever(matches, shared, &elm_version.into(), options)?;
+    let extras = util::add_extra_deps(matches, retriever)?;
+    util::add_hinted_versions(matches, retriever)?;
 
     retriever.add_preferred_versions(
         info.dependencies
@@ -46,36 +131,112 @@ fn solve_application(matches: &ArgMatches, offline: bool, info: Application) ->
         )
     }
 
-    Resolver::new(&mut retriever)
-        .solve()
-        .context(Kind::NoResolution)
-        .and_then(|x| serde_json::to_string(&AppDependencies::from(x)).context(Kind::Unknown))
-        .map(|v| println!("{}", v))?;
-    Ok(())
+    let started = Instant::now();
+    let res = util::solve(matches, options, &info, retriever)?;
+    util::report_timing(started, retriever);
+    util::check_licenses(matches, None, retriever, &res);
+
+    let mut direct_names: Vec<_> = info.dependencies.direct.keys().cloned().collect();
+    direct_names.extend(extras);
+    solution(matches, &direct_names, res)
 }
 
-fn solve_package(matches: &ArgMatches, offline: bool, info: Package) -> Result<()> {
+fn solve_package(
+    matches: &ArgMatches,
+    options: &Options,
+    shared: &mut Option<Retriever>,
+    info: Package,
+) -> Result<Solution> {
     let deps = if matches.is_present("test") {
         info.all_dependencies().context(Kind::InvalidElmJson)?
     } else {
         info.dependencies()
     };
 
-    let mut retriever =
-        Retriever::new(&info.elm_version().to_constraint(), offline).context(Kind::Unknown)?;
+    let retriever = retriever(
+        matches,
+        shared,
+        &info.elm_version().to_constraint(),
+        options,
+    )?;
 
     if matches.is_present("minimize") {
         retriever.minimize();
     }
 
-    let extras = util::add_extra_deps(matches, &mut retriever);
+    let extras = util::add_extra_deps(matches, retriever)?;
+    util::add_hinted_versions(matches, retriever)?;
 
     retriever.add_deps(deps.iter().filter(|(k, _)| !extras.contains(k)));
 
-    Resolver::new(&mut retriever)
-        .solve()
-        .context(Kind::NoResolution)
-        .and_then(|x| serde_json::to_string(&AppDependencies::from(x)).context(Kind::Unknown))
-        .map(|v| println!("{}", v))?;
-    Ok(())
+    let started = Instant::now();
+    let res = util::solve(matches, options, &info, retriever)?;
+    util::report_timing(started, retriever);
+    util::check_licenses(matches, Some(info.license()), retriever, &res);
+
+    let mut direct_names: Vec<_> = info.dependencies.keys().cloned().collect();
+    direct_names.extend(extras);
+    solution(matches, &direct_names, res)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Solution {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    elm_version: Option<semver::Version>,
+    #[serde(flatten)]
+    dependencies: Dependencies,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Dependencies {
+    Combined(AppDependencies),
+    #[serde(rename_all = "kebab-case")]
+    Separated {
+        dependencies: AppDependencies,
+        test_dependencies: AppDependencies,
+    },
+    /// Shaped like the `dependencies` of an application, with the test dependencies alongside.
+    #[serde(rename_all = "kebab-case")]
+    Flat {
+        direct: BTreeMap<package::Name, semver::Version>,
+        indirect: BTreeMap<package::Name, semver::Version>,
+        test_direct: BTreeMap<package::Name, semver::Version>,
+        test_indirect: BTreeMap<package::Name, semver::Version>,
+    },
+}
+
+fn solution(
+    matches: &ArgMatches,
+    direct_names: &[package::Name],
+    res: solver::Graph<solver::Summary<PackageId>>,
+) -> Result<Solution> {
+    // Elm reads an application's dependencies from these three keys, so `--elm-format` always
+    // includes the Elm version and keeps test-dependencies apart.
+    let elm_format = matches.is_present("elm-format");
+    let elm_version =
+        util::elm_version(&res).filter(|_| elm_format || matches.is_present("include-elm"));
+    let dependencies = if elm_format {
+        let (dependencies, test_dependencies) = project::reconstruct(direct_names, &res);
+        Dependencies::Separated {
+            dependencies,
+            test_dependencies,
+        }
+    } else if matches.is_present("separate-test") {
+        let (dependencies, test_dependencies) = project::reconstruct(direct_names, &res);
+        Dependencies::Flat {
+            direct: dependencies.direct,
+            indirect: dependencies.indirect,
+            test_direct: test_dependencies.direct,
+            test_indirect: test_dependencies.indirect,
+        }
+    } else {
+        Dependencies::Combined(AppDependencies::from(res))
+    };
+
+    Ok(Solution {
+        elm_version,
+        dependencies,
+    })
 }
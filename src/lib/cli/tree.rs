@@ -1,31 +1,34 @@
-use super::{util, Kind};
+use super::{error::NetworkContext, util, Kind};
 use crate::{
     package::{
         self,
-        retriever::{self, Retriever},
+        retriever::{self, Options, Retriever},
     },
-    project::{Application, Package},
-    semver,
-    solver::{self, Resolver},
+    project::{self, Application, Package},
+    semver::{self, Version},
+    solver,
 };
 use anyhow::{Context, Result};
 use clap::ArgMatches;
 use colored::Colorize;
 use itertools::Itertools;
-use petgraph::{self, visit::IntoNodeReferences};
-use std::collections::HashSet;
-use std::iter::FromIterator;
+use petgraph::{
+    self,
+    dot::{Config, Dot},
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Instant;
 
-pub fn run(matches: &ArgMatches, offline: bool) -> Result<()> {
-    util::with_elm_json(matches, offline, tree_application, tree_package)
+pub fn run(matches: &ArgMatches, options: &Options) -> Result<()> {
+    util::with_elm_json(matches, options, tree_application, tree_package)
 }
 
-fn tree_application(matches: &ArgMatches, offline: bool, info: Application) -> Result<()> {
+fn tree_application(matches: &ArgMatches, options: &Options, info: Application) -> Result<()> {
     let mut deps: Vec<_> = info.dependencies(&semver::Strictness::Exact);
     let elm_version = info.elm_version();
 
     let mut retriever: Retriever =
-        Retriever::new(&elm_version.into(), offline).context(Kind::Unknown)?;
+        Retriever::new(&elm_version.into(), options).network_context(Kind::Unknown)?;
 
     retriever.add_preferred_versions(
         info.dependencies
@@ -47,101 +50,403 @@ fn tree_application(matches: &ArgMatches, offline: bool, info: Application) -> R
 
     retriever.add_deps(&deps);
 
-    Resolver::new(&mut retriever)
-        .solve()
-        .map(|v| show_tree(&v, matches.value_of("package")))
-        .context(Kind::NoResolution)?;
+    let mut pinned: HashMap<retriever::PackageId, Version> = info
+        .dependencies
+        .direct
+        .iter()
+        .chain(info.dependencies.indirect.iter())
+        .map(|(k, v)| (k.clone().into(), *v))
+        .collect();
+
+    if matches.is_present("test") {
+        pinned.extend(
+            info.test_dependencies
+                .direct
+                .iter()
+                .chain(info.test_dependencies.indirect.iter())
+                .map(|(k, v)| (k.clone().into(), *v)),
+        );
+    }
+
+    pinned.insert(retriever::PackageId::Elm, elm_version);
+
+    // When every package is pinned, there's nothing left to solve, so we only need the
+    // dependencies of the pinned versions. Only if those don't line up do we run the resolver.
+    let started = Instant::now();
+    let pinned_graph = solver::build_graph(&mut retriever, |pkg| pinned.get(pkg).copied());
+    retriever.finish_progress();
+    let res = match pinned_graph.network_context(Kind::NoResolution)? {
+        Some(g) => g,
+        None => util::solve(matches, options, &info, &mut retriever)?,
+    };
+    util::report_timing(started, &retriever);
+
+    let test_deps = if matches.is_present("test") {
+        info.test_dependencies.direct.keys().cloned().collect()
+    } else {
+        HashSet::new()
+    };
+
+    show_tree(matches, &res, &test_deps);
     Ok(())
 }
 
-fn tree_package(matches: &ArgMatches, offline: bool, info: Package) -> Result<()> {
+fn tree_package(matches: &ArgMatches, options: &Options, info: Package) -> Result<()> {
     let deps = if matches.is_present("test") {
         info.all_dependencies().context(Kind::InvalidElmJson)?
     } else {
         info.dependencies()
     };
 
-    let mut retriever =
-        Retriever::new(&info.elm_version().to_constraint(), offline).context(Kind::Unknown)?;
+    let mut retriever = Retriever::new(&info.elm_version().to_constraint(), options)
+        .network_context(Kind::Unknown)?;
     retriever.add_deps(&deps);
 
-    Resolver::new(&mut retriever)
-        .solve()
-        .map(|v| show_tree(&v, matches.value_of("package")))
-        .context(Kind::NoResolution)?;
+    let started = Instant::now();
+    let res = util::solve(matches, options, &info, &mut retriever)?;
+    util::report_timing(started, &retriever);
+
+    let test_deps = if matches.is_present("test") {
+        info.test_dependencies.keys().cloned().collect()
+    } else {
+        HashSet::new()
+    };
+
+    show_tree(matches, &res, &test_deps);
     Ok(())
 }
 
-fn show_tree(g: &solver::Graph<solver::Summary<retriever::PackageId>>, target: Option<&str>) {
-    let root = g.node_references().next().unwrap().0;
-    if let Some(target) = target {
-        let name: package::Name = target.parse().expect("Invalid name parameter");
+/// `test_deps` are the direct test-dependencies, which are marked as such in the tree.
+fn show_tree(
+    matches: &ArgMatches,
+    g: &solver::Graph<solver::Summary<retriever::PackageId>>,
+    test_deps: &HashSet<package::Name>,
+) {
+    let root = match solver::root(g) {
+        Some(root) => root,
+        None => return,
+    };
+    let elm_version = util::elm_version(g).filter(|_| matches.is_present("include-elm"));
+    let connectors = if matches.is_present("ascii") {
+        &ASCII
+    } else {
+        &UNICODE
+    };
 
-        if let Some(target) = g.node_indices().find(|i| g[*i].id.is(&name)) {
-            let paths: Vec<Vec<_>> =
-                petgraph::algo::all_simple_paths(g, root, target, 0, None).collect();
-            let nodes: HashSet<_> = HashSet::from_iter(paths.concat());
-            let mut g = g.clone();
-            g.retain_nodes(|_, n| nodes.contains(&n));
-            print_graph(&g, root)
-        } else {
+    if let Some(name) = matches.value_of("reverse") {
+        let target = g.node_indices().find(|i| match &g[*i].id {
+            retriever::PackageId::Pkg(pkg) => pkg.to_string() == name,
+            _ => false,
+        });
+
+        match target {
+            None => println!(
+                "Could not find {} in direct or indirect dependencies.",
+                name
+            ),
+            Some(target) if matches.is_present("dot") || matches.is_present("json") => {
+                let mut dependents = HashSet::new();
+                let reversed = petgraph::visit::Reversed(g);
+                let mut bfs = petgraph::visit::Bfs::new(reversed, target);
+                while let Some(nx) = bfs.next(reversed) {
+                    dependents.insert(nx);
+                }
+                let mut g = g.clone();
+                g.retain_nodes(|_, n| dependents.contains(&n));
+                render(matches, &g, root, elm_version, connectors, test_deps)
+            }
+            Some(target) => print_reverse(g, target, connectors),
+        }
+    } else if let Some(filter) = matches.value_of("package") {
+        // The validator made sure any version after the @ parses.
+        let (pattern, version) = match filter.split_once('@') {
+            Some((pattern, version)) => (pattern, version.parse::<Version>().ok()),
+            None => (filter, None),
+        };
+        let targets: Vec<_> = g
+            .node_indices()
+            .filter(|i| match &g[*i].id {
+                retriever::PackageId::Pkg(name) => {
+                    glob_matches(pattern, &name.to_string())
+                        && version.is_none_or(|version| g[*i].version == version)
+                }
+                _ => false,
+            })
+            .collect();
+
+        if targets.is_empty() {
             println!(
                 "Could not find {} in direct or indirect dependencies.",
-                target
+                filter
             )
+        } else {
+            let nodes: HashSet<_> = targets
+                .into_iter()
+                .flat_map(|target| {
+                    petgraph::algo::all_simple_paths::<Vec<_>, _>(g, root, target, 0, None)
+                })
+                .flatten()
+                .collect();
+            let mut g = g.clone();
+            g.retain_nodes(|_, n| nodes.contains(&n));
+            render(matches, &g, root, elm_version, connectors, test_deps)
         }
     } else {
-        print_graph(g, root)
+        render(matches, g, root, elm_version, connectors, test_deps)
     }
 }
 
+/// Match a package name against a pattern where `*` stands for any (possibly empty) sequence of
+/// characters. Patterns without a `*` only match exactly.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match name.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.is_empty()
+}
+
+fn render(
+    matches: &ArgMatches,
+    g: &solver::Graph<solver::Summary<retriever::PackageId>>,
+    root: petgraph::graph::NodeIndex,
+    elm_version: Option<Version>,
+    connectors: &Connectors,
+    test_deps: &HashSet<package::Name>,
+) {
+    if matches.is_present("dot") {
+        print_dot(g, elm_version.is_some())
+    } else if matches.is_present("json") {
+        print_json(g, elm_version)
+    } else {
+        print_graph(g, root, elm_version, connectors, test_deps)
+    }
+}
+
+fn print_dot(g: &solver::Graph<solver::Summary<retriever::PackageId>>, include_elm: bool) {
+    let labelled = g.filter_map(
+        |_, item| match &item.id {
+            retriever::PackageId::Root => Some("project".to_string()),
+            retriever::PackageId::Elm if include_elm => Some(format!("elm@{}", item.version)),
+            retriever::PackageId::Elm => None,
+            retriever::PackageId::Pkg(name) => Some(format!("{}@{}", name, item.version)),
+        },
+        |_, _| Some(""),
+    );
+
+    println!("{}", Dot::with_config(&labelled, &[Config::EdgeNoLabel]));
+}
+
+/// The packages in the (possibly filtered) graph and their versions, as a flat JSON object.
+fn print_json(
+    g: &solver::Graph<solver::Summary<retriever::PackageId>>,
+    elm_version: Option<Version>,
+) {
+    let mut versions: BTreeMap<String, Version> = project::flatten(g)
+        .into_iter()
+        .map(|(name, version)| (name.to_string(), version))
+        .collect();
+    if let Some(elm_version) = elm_version {
+        versions.insert("elm".to_string(), elm_version);
+    }
+
+    println!(
+        "{}",
+        serde_json::to_string(&versions).expect("Failed to serialize versions")
+    );
+}
+
+struct Connectors {
+    branch: &'static str,
+    last: &'static str,
+    continued: &'static str,
+    empty: &'static str,
+}
+
+const UNICODE: Connectors = Connectors {
+    branch: "\u{251c}\u{2500}\u{2500}",
+    last: "\u{2514}\u{2500}\u{2500}",
+    continued: "\u{2502}   ",
+    empty: "    ",
+};
+
+const ASCII: Connectors = Connectors {
+    branch: "|--",
+    last: "`--",
+    continued: "|   ",
+    empty: "    ",
+};
+
 fn print_graph(
     g: &solver::Graph<solver::Summary<retriever::PackageId>>,
     root: petgraph::graph::NodeIndex,
+    elm_version: Option<Version>,
+    connectors: &Connectors,
+    test_deps: &HashSet<package::Name>,
 ) {
     let mut visited: HashSet<usize> = HashSet::new();
     println!("\nproject");
 
-    visit_children("", g, &mut visited, root);
+    if let Some(elm_version) = elm_version {
+        let connector = if g
+            .neighbors(root)
+            .any(|idx| matches!(&g[idx].id, retriever::PackageId::Pkg(_)))
+        {
+            connectors.branch
+        } else {
+            connectors.last
+        };
+        println!("{} {} @ {}", connector, "elm".bold(), elm_version);
+    }
+
+    if !g
+        .neighbors(root)
+        .any(|idx| matches!(&g[idx].id, retriever::PackageId::Pkg(_)))
+    {
+        println!("\nThis project doesn't have any dependencies.");
+        return;
+    }
+
+    visit_children(
+        "",
+        g,
+        &mut visited,
+        root,
+        connectors,
+        test_deps,
+        petgraph::Direction::Outgoing,
+    );
 
+    if !test_deps.is_empty() {
+        println!(
+            "\nItems marked with {} are test-dependencies.",
+            "(test)".yellow()
+        );
+    }
     println!("\nItems marked with {} have their dependencies ommitted since they've already\nappeared in the output.", "*".blue());
 }
 
+/// Everything that (transitively) depends on `target`, as a tree rooted at `target`.
+fn print_reverse(
+    g: &solver::Graph<solver::Summary<retriever::PackageId>>,
+    target: petgraph::graph::NodeIndex,
+    connectors: &Connectors,
+) {
+    let mut visited: HashSet<usize> = HashSet::new();
+    println!("\n{} @ {}", g[target].id, g[target].version);
+
+    visit_children(
+        "",
+        g,
+        &mut visited,
+        target,
+        connectors,
+        &HashSet::new(),
+        petgraph::Direction::Incoming,
+    );
+
+    println!("\nItems marked with {} have their dependents ommitted since they've already\nappeared in the output.", "*".blue());
+}
+
+/// Walk the graph from `root`, following dependencies when `direction` is `Outgoing` and
+/// dependents when it is `Incoming`.
 fn visit_children(
     prefix: &str,
     g: &solver::Graph<solver::Summary<retriever::PackageId>>,
     visited: &mut HashSet<usize>,
     root: petgraph::graph::NodeIndex,
+    connectors: &Connectors,
+    test_deps: &HashSet<package::Name>,
+    direction: petgraph::Direction,
 ) {
+    let is_top_level = g[root].id == retriever::PackageId::Root;
     let mut graph_iter = g
-        .neighbors_directed(root, petgraph::Direction::Outgoing)
-        .filter(|&idx| matches!(&g[idx].id, retriever::PackageId::Pkg(_)))
+        .neighbors_directed(root, direction)
+        .filter(|&idx| match &g[idx].id {
+            retriever::PackageId::Pkg(_) => true,
+            retriever::PackageId::Root => direction == petgraph::Direction::Incoming,
+            retriever::PackageId::Elm => false,
+        })
         .sorted_by(|&a, &b| Ord::cmp(&g[a].id, &g[b].id))
         .peekable();
 
     while let Some(idx) = graph_iter.next() {
         let item = &g[idx];
-        let repeated = visited.contains(&idx.index()) && g.edges(idx).next().is_some();
+        let repeated =
+            visited.contains(&idx.index()) && g.edges_directed(idx, direction).next().is_some();
         visited.insert(idx.index());
 
-        if let retriever::PackageId::Pkg(name) = &item.id {
-            let (s, e) = if graph_iter.peek().is_some() {
-                ("\u{251c}\u{2500}\u{2500}", "\u{2502}   ")
-            } else {
-                ("\u{2514}\u{2500}\u{2500}", "    ")
-            };
-            println!(
-                "{}{} {} @ {}{}",
+        let (s, e) = if graph_iter.peek().is_some() {
+            (connectors.branch, connectors.continued)
+        } else {
+            (connectors.last, connectors.empty)
+        };
+
+        match &item.id {
+            retriever::PackageId::Pkg(name) => println!(
+                "{}{} {} @ {}{}{}",
                 prefix,
                 s,
                 name,
                 item.version,
+                if is_top_level && test_deps.contains(name) {
+                    " (test)".yellow()
+                } else {
+                    "".clear()
+                },
                 if repeated { " *".blue() } else { "".clear() }
-            );
+            ),
+            _ => println!("{}{} project", prefix, s),
+        }
 
-            if !repeated {
-                visit_children(&(prefix.to_owned() + e), g, visited, idx)
-            }
+        if !repeated {
+            visit_children(
+                &(prefix.to_owned() + e),
+                g,
+                visited,
+                idx,
+                connectors,
+                test_deps,
+                direction,
+            )
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::glob_matches;
+
+    #[test]
+    fn exact_patterns() {
+        assert!(glob_matches("elm/core", "elm/core"));
+        assert!(!glob_matches("elm/core", "elm/core-extra"));
+        assert!(!glob_matches("elm/cor", "elm/core"));
+    }
+
+    #[test]
+    fn wildcard_patterns() {
+        assert!(glob_matches("elm/*", "elm/core"));
+        assert!(!glob_matches("elm/*", "elm-community/list-extra"));
+        assert!(glob_matches("*html*", "elm/html"));
+        assert!(glob_matches("*html*", "rtfeldman/elm-css-html"));
+        assert!(glob_matches("*/json", "elm/json"));
+        assert!(glob_matches("elm*/*extra", "elm-community/list-extra"));
+        assert!(!glob_matches("*/json", "elm/json-extra"));
+        assert!(glob_matches("*", "elm/core"));
+    }
+}
@@ -1,26 +1,31 @@
-use super::{util, Kind};
+use super::{error::NetworkContext, util, Kind};
 use crate::{
     diff,
-    package::{self, retriever::Retriever},
+    package::{
+        self,
+        retriever::{Options, PackageId, Retriever},
+    },
     project::{self, Application, Package, Project},
-    semver,
-    solver::Resolver,
+    semver, solver,
 };
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use clap::ArgMatches;
 use colored::Colorize;
+use dialoguer::Confirm;
+use itertools::Itertools;
 use std::collections::{BTreeMap, HashSet};
+use std::time::Instant;
 
-pub fn run(matches: &ArgMatches, offline: bool) -> Result<()> {
-    util::with_elm_json(matches, offline, uninstall_application, uninstall_package)
+pub fn run(matches: &ArgMatches, options: &Options) -> Result<()> {
+    util::with_elm_json(matches, options, uninstall_application, uninstall_package)
 }
 
-fn uninstall_application(matches: &ArgMatches, offline: bool, info: Application) -> Result<()> {
+fn uninstall_application(matches: &ArgMatches, options: &Options, info: Application) -> Result<()> {
     let strictness = semver::Strictness::Exact;
     let elm_version = info.elm_version();
 
     let mut retriever: Retriever =
-        Retriever::new(&elm_version.into(), offline).context(Kind::Unknown)?;
+        Retriever::new(&elm_version.into(), options).network_context(Kind::Unknown)?;
 
     let extras: HashSet<_> = matches
         .values_of_lossy("extra")
@@ -57,9 +62,9 @@ fn uninstall_application(matches: &ArgMatches, offline: bool, info: Application)
             .filter(|(k, _)| !extras.contains(k)),
     );
 
-    let res = Resolver::new(&mut retriever)
-        .solve()
-        .context(Kind::NoResolution)?;
+    let started = Instant::now();
+    let res = util::resolve(options, &mut retriever)?;
+    util::report_timing(started, &retriever);
 
     let orig_direct = info
         .dependencies
@@ -97,8 +102,16 @@ fn uninstall_application(matches: &ArgMatches, offline: bool, info: Application)
         &deps.1.indirect,
     );
 
+    show_leftovers(&extras, &res);
+
+    let removed = names(&info.dependencies, &info.test_dependencies)
+        .difference(&names(&deps.0, &deps.1))
+        .count();
+
     let updated = Project::Application(info.with(deps.0, deps.1));
-    if util::confirm("Should I make these changes?", matches)? {
+    if util::confirm("Should I make these changes?", matches)?
+        && confirm_removals(removed, matches)?
+    {
         util::write_elm_json(&updated, matches)?;
         println!("Saved updated elm.json!");
     } else {
@@ -108,7 +121,77 @@ fn uninstall_application(matches: &ArgMatches, offline: bool, info: Application)
     Ok(())
 }
 
-fn uninstall_package(matches: &ArgMatches, _offline: bool, info: Package) -> Result<()> {
+fn names<'a>(
+    deps: &'a project::AppDependencies,
+    test_deps: &'a project::AppDependencies,
+) -> HashSet<&'a package::Name> {
+    [deps, test_deps]
+        .iter()
+        .flat_map(|d| d.direct.keys().chain(d.indirect.keys()))
+        .collect()
+}
+
+/// Removing more than `--max-removals` packages asks for confirmation once more, even with `--yes`,
+/// so a single uninstall can't quietly gut a project. `--force` skips this.
+fn confirm_removals(removed: usize, matches: &ArgMatches) -> Result<bool> {
+    let max = match matches.value_of("max-removals") {
+        Some(max) => max.parse::<usize>().context(Kind::InvalidArgument)?,
+        None => return Ok(true),
+    };
+
+    if removed <= max || matches.is_present("force") {
+        return Ok(true);
+    }
+
+    let warning = format!(
+        "This removes {} packages from elm.json, more than the {} allowed by --max-removals.",
+        removed, max
+    );
+    if super::noninteractive() || !console::user_attended() {
+        return Err(anyhow!("{} Use --force to remove them anyway.", warning))
+            .context(Kind::InvalidArgument);
+    }
+
+    Confirm::new()
+        .with_prompt(format!("{} Are you sure?", warning))
+        .interact()
+        .context(Kind::Unknown)
+}
+
+/// Uninstalling a package only drops it from elm.json if nothing else depends on it, so tell which
+/// of the requested packages are really gone and which ones stick around, and why.
+fn show_leftovers(extras: &HashSet<package::Name>, g: &solver::Graph<solver::Summary<PackageId>>) {
+    let mut extras: Vec<_> = extras.iter().collect();
+    extras.sort();
+
+    for name in extras {
+        let node = g
+            .node_indices()
+            .find(|&idx| matches!(&g[idx].id, PackageId::Pkg(pkg) if pkg == name));
+
+        match node {
+            None => println!("- {} was removed entirely", name),
+            Some(idx) => {
+                let dependents: Vec<_> = g
+                    .neighbors_directed(idx, petgraph::Direction::Incoming)
+                    .map(|dependent| match &g[dependent].id {
+                        PackageId::Pkg(pkg) => pkg.to_string(),
+                        _ => "this project".to_string(),
+                    })
+                    .sorted()
+                    .collect();
+                println!(
+                    "- {} is still needed by {}, so it stays in elm.json",
+                    name,
+                    dependents.join(", ")
+                );
+            }
+        }
+    }
+    println!();
+}
+
+fn uninstall_package(matches: &ArgMatches, _options: &Options, info: Package) -> Result<()> {
     let extras: HashSet<_> = matches
         .values_of_lossy("extra")
         .unwrap_or_default()
@@ -138,8 +221,14 @@ fn uninstall_package(matches: &ArgMatches, _offline: bool, info: Package) -> Res
     diff::show(diff::Kind::Regular, &info.dependencies, &new_deps);
     diff::show(diff::Kind::Test, &info.test_dependencies, &new_test_deps);
 
+    let removed = info.dependencies.len() + info.test_dependencies.len()
+        - new_deps.len()
+        - new_test_deps.len();
+
     let updated = Project::Package(info.with_deps(new_deps, new_test_deps));
-    if util::confirm("Should I make these changes?", matches)? {
+    if util::confirm("Should I make these changes?", matches)?
+        && confirm_removals(removed, matches)?
+    {
         util::write_elm_json(&updated, matches)?;
         println!("Saved!");
     } else {
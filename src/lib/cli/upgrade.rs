@@ -1,21 +1,28 @@
-use super::{util, Kind};
+use super::{error::NetworkContext, util, Kind};
 use crate::{
     diff,
-    package::retriever::Retriever,
-    project::{self, Application, Project},
+    package::retriever::{Options, Retriever},
+    project::{self, AppDependencies, Application, Project},
     semver,
-    solver::Resolver,
 };
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use clap::ArgMatches;
 use colored::Colorize;
+use std::time::Instant;
 
-pub fn run(matches: &ArgMatches, offline: bool) -> Result<()> {
-    util::with_elm_json(matches, offline, upgrade_application, |_, _, _| {
+pub fn run(matches: &ArgMatches, options: &Options) -> Result<()> {
+    if util::elm_json_type(matches)? == "package" {
+        return Err(anyhow!(
+            "Packages depend on ranges of versions rather than exact ones, so only the elm.json of an application can be upgraded."
+        ))
+        .context(Kind::NotSupported);
+    }
+
+    util::with_elm_json(matches, options, upgrade_application, |_, _, _| {
         bail!(Kind::NotSupported)
     })
 }
-fn upgrade_application(matches: &ArgMatches, offline: bool, info: Application) -> Result<()> {
+fn upgrade_application(matches: &ArgMatches, options: &Options, info: Application) -> Result<()> {
     let strictness = if matches.is_present("unsafe") {
         semver::Strictness::Unsafe
     } else {
@@ -24,14 +31,27 @@ fn upgrade_application(matches: &ArgMatches, offline: bool, info: Application) -
     let elm_version = info.elm_version();
 
     let mut retriever: Retriever =
-        Retriever::new(&elm_version.into(), offline).context(Kind::Unknown)?;
+        Retriever::new(&elm_version.into(), options).network_context(Kind::Unknown)?;
 
     retriever.add_deps(&info.dependencies(&strictness));
-    retriever.add_deps(&info.test_dependencies(&strictness));
 
-    let res = Resolver::new(&mut retriever)
-        .solve()
-        .context(Kind::NoResolution)?;
+    if matches.is_present("ignore-test") {
+        // Pin the test-dependencies to their current versions, so only production dependencies
+        // move.
+        retriever.add_deps(&info.test_dependencies(&semver::Strictness::Exact));
+        retriever.add_preferred_versions(
+            info.test_dependencies
+                .indirect
+                .iter()
+                .map(|(k, v)| (k.clone().into(), *v)),
+        );
+    } else {
+        retriever.add_deps(&info.test_dependencies(&strictness));
+    }
+
+    let started = Instant::now();
+    let res = util::resolve(options, &mut retriever)?;
+    util::report_timing(started, &retriever);
 
     let direct_deps: Vec<_> = info.dependencies.direct.keys().cloned().collect();
     let deps = project::reconstruct(&direct_deps, &res);
@@ -47,27 +67,48 @@ fn upgrade_application(matches: &ArgMatches, offline: bool, info: Application) -
         util::format_header("PACKAGE UPGRADES FOUND").green()
     );
 
-    diff::show(
+    let direct = diff::show(
         diff::Kind::Direct,
         &info.dependencies.direct,
         &deps.0.direct,
     );
-    diff::show(
+    let indirect = diff::show(
         diff::Kind::Indirect,
         &info.dependencies.indirect,
         &deps.0.indirect,
     );
-    diff::show(
+    let direct_test = diff::show(
         diff::Kind::DirectTest,
         &info.test_dependencies.direct,
         &deps.1.direct,
     );
-    diff::show(
+    let indirect_test = diff::show(
         diff::Kind::IndirectTest,
         &info.test_dependencies.indirect,
         &deps.1.indirect,
     );
 
+    println!(
+        "{} direct, {} indirect changed.\n",
+        direct + direct_test,
+        indirect + indirect_test
+    );
+
+    show_unstable(&info, &deps.0, &deps.1);
+    show_disjoint(&info, &deps.0, &deps.1);
+
+    if info.is_frozen()
+        && (deps.0.indirect != info.dependencies.indirect
+            || deps.1.indirect != info.test_dependencies.indirect)
+    {
+        println!(
+            "{} your indirect dependencies were frozen with `elm-json install --freeze`, but this upgrade changes them.\n",
+            "Warning:".yellow()
+        );
+    }
+
+    util::check_frozen(matches)?;
+
     let updated = Project::Application(info.with(deps.0, deps.1));
     if util::confirm("Should I make these changes?", matches)? {
         util::write_elm_json(&updated, matches)?;
@@ -78,3 +119,64 @@ fn upgrade_application(matches: &ArgMatches, offline: bool, info: Application) -
 
     Ok(())
 }
+
+fn show_unstable(info: &Application, deps: &AppDependencies, test_deps: &AppDependencies) {
+    let unstable: Vec<_> = deps
+        .direct
+        .iter()
+        .filter(|&(k, v)| info.dependencies.direct.get(k) != Some(v))
+        .chain(
+            test_deps
+                .direct
+                .iter()
+                .filter(|&(k, v)| info.test_dependencies.direct.get(k) != Some(v)),
+        )
+        .filter(|(_, v)| !v.is_stable())
+        .collect();
+
+    if !unstable.is_empty() {
+        println!(
+            "{} these packages are still pre-1.0, where minor bumps may include breaking changes:\n",
+            "Note:".bold()
+        );
+        for (k, v) in unstable {
+            println!("- {} {}", k, v);
+        }
+        println!();
+    }
+}
+
+fn show_disjoint(info: &Application, deps: &AppDependencies, test_deps: &AppDependencies) {
+    let compatible_with = |v: &semver::Version| {
+        semver::Constraint::from(semver::Range::from(v, &semver::Strictness::Safe))
+    };
+
+    let disjoint: Vec<_> = info
+        .dependencies
+        .direct
+        .iter()
+        .filter_map(|(k, old)| deps.direct.get(k).map(|new| (k, old, new)))
+        .chain(
+            info.test_dependencies
+                .direct
+                .iter()
+                .filter_map(|(k, old)| test_deps.direct.get(k).map(|new| (k, old, new))),
+        )
+        .filter(|(_, old, new)| {
+            !compatible_with(old)
+                .relation(&compatible_with(new))
+                .is_compatible()
+        })
+        .collect();
+
+    if !disjoint.is_empty() {
+        println!(
+            "{} these upgrades move to versions that aren't compatible with the previous ones, so they may include breaking changes:\n",
+            "Warning:".yellow()
+        );
+        for (k, old, new) in disjoint {
+            println!("- {} {} -> {}", k, old, new);
+        }
+        println!();
+    }
+}
@@ -1,22 +1,31 @@
-use super::Kind;
+use super::{config, error::NetworkContext, Kind};
 use crate::{
-    package::{self, retriever::Retriever},
-    project::{Application, Package, Project},
+    interrupt,
+    package::{
+        self,
+        retriever::{self, Options, PackageId, Retriever},
+    },
+    project::{self, Application, Package, Project},
     semver,
+    solver::{self, Resolver},
 };
 use anyhow::{anyhow, Context, Result};
 use clap::ArgMatches;
+use colored::Colorize;
 use dialoguer::Confirm;
-use serde::ser::Serialize;
+use serde::Serialize;
 use std::{
-    collections::HashSet,
-    convert,
-    fs::File,
-    io::{BufWriter, Write},
+    collections::{hash_map::DefaultHasher, BTreeMap, HashSet},
+    convert, fs,
+    hash::{Hash, Hasher},
+    io,
+    path::Path,
+    time::{Duration, Instant},
 };
+use tracing::{debug, warn, Level};
 
 pub fn confirm(prompt: &str, matches: &ArgMatches) -> Result<bool> {
-    if matches.is_present("yes") {
+    if matches.is_present("yes") || super::noninteractive() {
         return Ok(true);
     }
     Confirm::new()
@@ -26,67 +35,275 @@ pub fn confirm(prompt: &str, matches: &ArgMatches) -> Result<bool> {
         .map_err(convert::Into::into)
 }
 
+/// With `--frozen` or `--check`, elm.json is expected to be up to date already, so any change we
+/// were about to make is an error rather than something to confirm.
+pub fn check_frozen(matches: &ArgMatches) -> Result<()> {
+    match ["frozen", "check"]
+        .iter()
+        .find(|flag| matches.is_present(flag))
+    {
+        Some(flag) => Err(anyhow!(
+            "Your elm.json would change as shown above, which isn't allowed with --{0}. Run the same command without --{0} to apply these changes.",
+            flag
+        ))
+        .context(Kind::OutOfDate),
+        None => Ok(()),
+    }
+}
+
 pub fn with_elm_json<A, P>(
     matches: &ArgMatches,
-    offline: bool,
+    options: &Options,
     run_app: A,
     run_pkg: P,
 ) -> Result<()>
 where
-    A: FnOnce(&ArgMatches, bool, Application) -> Result<()>,
-    P: FnOnce(&ArgMatches, bool, Package) -> Result<()>,
+    A: FnOnce(&ArgMatches, &Options, Application) -> Result<()>,
+    P: FnOnce(&ArgMatches, &Options, Package) -> Result<()>,
 {
     match self::read_elm_json(matches)? {
-        Project::Application(app) => run_app(matches, offline, app),
-        Project::Package(pkg) => run_pkg(matches, offline, pkg),
+        Project::Application(app) => run_app(matches, options, app),
+        Project::Package(pkg) => run_pkg(matches, options, pkg),
     }
 }
 
 fn read_elm_json(matches: &ArgMatches) -> Result<Project> {
     let path = matches.value_of("INPUT").unwrap();
-    let file = File::open(path).context(Kind::MissingElmJson)?;
-    let info: Project = serde_json::from_reader(file).context(Kind::InvalidElmJson)?;
-    Ok(info)
+    if matches.is_present("allow-comments") && has_comments(path) {
+        eprintln!(
+            "{} ignoring the comments in {}. If it's changed, they won't be kept.\n",
+            "Warning:".yellow(),
+            path
+        );
+    }
+    read_elm_json_at(path, matches)
+}
+
+/// With `--allow-comments`, comments in the file are ignored rather than being a parse error.
+pub fn read_elm_json_at(path: &str, matches: &ArgMatches) -> Result<Project> {
+    let project = if matches.is_present("allow-comments") {
+        Project::load_with_comments(Path::new(path))
+    } else {
+        Project::load(Path::new(path))
+    }
+    .map_err(elm_json_error)?;
+
+    // An Elm version no package targets would otherwise only surface as a resolution failure.
+    // With `--no-elm` the project's Elm version isn't used, so there's nothing to complain about.
+    if let Project::Application(app) = &project {
+        let known = retriever::known_elm_versions();
+        if !matches.is_present("no-elm") && !known.contains(&app.elm_version()) {
+            let known: Vec<_> = known.iter().map(ToString::to_string).collect();
+            return Err(anyhow!(
+                "{} says it's for Elm {}, which isn't a version I know about. Elm versions I know about are {}.",
+                path,
+                app.elm_version(),
+                known.join(", ")
+            ))
+            .context(Kind::InvalidElmJson);
+        }
+    }
+    Ok(project)
+}
+
+fn has_comments(path: &str) -> bool {
+    fs::read_to_string(path)
+        .map(|contents| project::strip_comments(&contents) != contents)
+        .unwrap_or(false)
+}
+
+/// Whether the elm.json is an `"application"` or a `"package"`, without requiring the rest of it
+/// to be valid.
+pub fn elm_json_type(matches: &ArgMatches) -> Result<&'static str> {
+    Project::detect_type(Path::new(matches.value_of("INPUT").unwrap())).map_err(elm_json_error)
+}
+
+fn elm_json_error(e: anyhow::Error) -> anyhow::Error {
+    if e.is::<io::Error>() {
+        e.context(Kind::MissingElmJson)
+    } else {
+        e.context(Kind::InvalidElmJson)
+    }
 }
 
 pub fn write_elm_json(project: &Project, matches: &ArgMatches) -> Result<()> {
     let path = matches.value_of("INPUT").unwrap();
-    let file = File::create(path).context(Kind::UnwritableElmJson)?;
-    let writer = BufWriter::new(file);
-    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
-    let mut serializer = serde_json::Serializer::with_formatter(writer, formatter);
-    project.serialize(&mut serializer).context(Kind::Unknown)?;
-    let mut writer = serializer.into_inner();
-    writer.write(b"\n").context(Kind::UnwritableElmJson)?;
-    writer.flush().context(Kind::UnwritableElmJson)?;
-    Ok(())
+    let indent = match matches.value_of("indent") {
+        Some(n) => " ".repeat(
+            parse_indent(n)
+                .map_err(|e| anyhow!("Invalid --indent {}: {}", n, e))
+                .context(Kind::InvalidArgument)?,
+        ),
+        None => config::get()
+            .indent()
+            .or_else(|| detect_indent(path))
+            .unwrap_or_else(|| "    ".to_string()),
+    };
+    let order = if matches.is_present("sort-ignore-case") {
+        package::DependencyOrder::IgnoreCase
+    } else {
+        package::DependencyOrder::Canonical
+    };
+    project
+        .save_with(Path::new(path), &indent, order)
+        .map_err(|e| {
+            if e.is::<io::Error>() {
+                e.context(Kind::UnwritableElmJson)
+            } else if e.is::<interrupt::Interrupted>() {
+                e.context(Kind::Interrupted)
+            } else {
+                e.context(Kind::Unknown)
+            }
+        })
+}
+
+/// The whitespace used for a single level of indentation in an existing file, if any.
+fn detect_indent(path: &str) -> Option<String> {
+    let contents = fs::read_to_string(path).ok()?;
+    contents
+        .lines()
+        .map(|line| {
+            line.chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .collect::<String>()
+        })
+        .find(|indent| !indent.is_empty())
 }
 
-pub fn add_extra_deps(matches: &ArgMatches, retriever: &mut Retriever) -> HashSet<package::Name> {
-    let mut extras = HashSet::new();
+pub fn add_extra_deps(
+    matches: &ArgMatches,
+    retriever: &mut Retriever,
+) -> Result<HashSet<package::Name>> {
+    // Requested packages with the version they were requested at, if any.
+    let mut requested: BTreeMap<package::Name, Option<(&str, semver::Constraint)>> =
+        BTreeMap::new();
+    let deps = matches.values_of_lossy("extra").unwrap_or_default();
+
+    for dep in &deps {
+        let expanded = expand_project_name(dep, retriever, matches)?;
+        let (name, version) = parse_extra(&expanded)
+            .with_context(|| format!("I couldn't make sense of the package {}", dep))
+            .context(Kind::InvalidArgument)?;
+        let version = version.map(|v| (dep.split_once('@').map_or("", |(_, raw)| raw), v));
 
-    for dep in &matches.values_of_lossy("extra").unwrap_or_default() {
-        let parts: Vec<&str> = dep.split('@').collect();
-        match parts.as_slice() {
-            [name] => {
-                let name: package::Name = name.parse().expect("Invalid name parameter");
-                retriever.add_dep(name.clone(), None);
-                extras.insert(name);
+        match (requested.get(&name), version) {
+            (Some(Some((previous, a))), Some((current, b))) if a != &b => {
+                return Err(anyhow!(
+                    "You asked for {} at both {} and {}, please pick one.",
+                    name,
+                    previous,
+                    current
+                ))
+                .context(Kind::InvalidArgument);
             }
-            [name, version] => {
-                let version: semver::Constraint = version
-                    .parse::<semver::Version>()
-                    .map(semver::Constraint::from)
-                    .or_else(|_| lax_version_from_string(version))
-                    .expect("Invalid version specifier");
-                let name: package::Name = name.parse().expect("Invalid name parameter");
-                retriever.add_dep(name.clone(), Some(version));
-                extras.insert(name);
+            (Some(Some(_)), _) => {}
+            (_, version) => {
+                requested.insert(name, version);
             }
-            _ => unreachable!(),
         }
     }
-    extras
+
+    for (name, version) in &requested {
+        retriever.add_dep(name.clone(), version.as_ref().map(|(_, v)| v.clone()));
+    }
+    Ok(requested.into_keys().collect())
+}
+
+/// Versions passed with `--prefer` are tried first, but other versions are still considered when
+/// they don't fit.
+pub fn add_hinted_versions(matches: &ArgMatches, retriever: &mut Retriever) -> Result<()> {
+    let hints = matches
+        .values_of("prefer")
+        .into_iter()
+        .flatten()
+        .map(|hint| {
+            parse_exact_package(hint)
+                .with_context(|| format!("I couldn't make sense of the version {}", hint))
+                .context(Kind::InvalidArgument)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    retriever.add_hinted_versions(hints.into_iter().map(|(name, v)| (name.into(), v)));
+    Ok(())
+}
+
+/// Parse a package with an exact version, e.g. `elm/core@1.0.5`.
+pub fn parse_exact_package(dep: &str) -> Result<(package::Name, semver::Version)> {
+    match dep.split_once('@') {
+        Some((name, version)) => Ok((name.parse()?, version.parse()?)),
+        None => Err(anyhow!(
+            "Expected a package name followed by @ and an exact version"
+        )),
+    }
+}
+
+/// Packages can be given by their project name alone, e.g. `http`, in which case we look for the
+/// author among the known packages, asking which one is meant if there are several.
+fn expand_project_name(dep: &str, retriever: &Retriever, matches: &ArgMatches) -> Result<String> {
+    let (project, version) = match dep.split_once('@') {
+        Some((project, version)) => (project, Some(version)),
+        None => (dep, None),
+    };
+    if project.contains('/') {
+        return Ok(dep.to_string());
+    }
+
+    let candidates: Vec<package::Name> = retriever
+        .latest_versions()
+        .into_keys()
+        .filter(|name| name.project() == project)
+        .collect();
+
+    let name = match candidates.as_slice() {
+        [] => {
+            return Err(anyhow!("I couldn't find any package called {}.", project))
+                .context(Kind::InvalidArgument)
+        }
+        [name] => name,
+        _ if matches.is_present("yes") || super::noninteractive() || !console::user_attended() => {
+            return Err(anyhow!(
+                "There are several packages called {}, please pick one of {}.",
+                project,
+                candidates
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .context(Kind::InvalidArgument)
+        }
+        _ => {
+            let idx = dialoguer::Select::new()
+                .with_prompt(format!("Which {} package do you mean?", project))
+                .items(&candidates)
+                .default(0)
+                .interact()
+                .context(Kind::Unknown)?;
+            &candidates[idx]
+        }
+    };
+
+    Ok(match version {
+        Some(version) => format!("{}@{}", name, version),
+        None => name.to_string(),
+    })
+}
+
+fn parse_extra(dep: &str) -> Result<(package::Name, Option<semver::Constraint>)> {
+    let parts: Vec<&str> = dep.split('@').collect();
+    match parts.as_slice() {
+        [name] => Ok((name.parse()?, None)),
+        [name, version] => {
+            let version: semver::Constraint = version
+                .parse::<semver::Version>()
+                .map(semver::Constraint::from)
+                .or_else(|_| lax_version_from_string(version))
+                .map_err(|e| anyhow!("{}", e))?;
+            Ok((name.parse()?, Some(version)))
+        }
+        _ => Err(anyhow!(
+            "Expected a package name, optionally followed by @ and a version"
+        )),
+    }
 }
 
 fn lax_version_from_string(version: &str) -> std::result::Result<semver::Constraint, String> {
@@ -95,15 +312,35 @@ fn lax_version_from_string(version: &str) -> std::result::Result<semver::Constra
         .map(str::parse)
         .collect::<std::result::Result<Vec<_>, _>>()
         .map_err(|e| anyhow!("{}", e).to_string())?;
-    match parts.as_slice() {
-        [major] => Ok(semver::Constraint::from(semver::Range::from(
-            &semver::Version::new(*major, 0, 0),
-            &semver::Strictness::Safe,
-        ))),
-        _ => Err("Expected a valid lax version spec".into()),
+    // The upper bound is one past the given major or minor version, so that has to fit.
+    if parts.contains(&u64::MAX) {
+        return Err("Expected a valid lax version spec".into());
     }
+    let (lower, upper) = match parts.as_slice() {
+        [0] => return Err(ZERO_MAJOR_LAX_VERSION.into()),
+        [major] => {
+            let lower = semver::Version::new(*major, 0, 0);
+            (lower, lower.next_major())
+        }
+        // Before 1.0.0, minor versions may break things, so stick to the given one.
+        [0, minor] => {
+            let lower = semver::Version::new(0, *minor, 0);
+            (lower, lower.next_minor())
+        }
+        [major, minor] => {
+            let lower = semver::Version::new(*major, *minor, 0);
+            (lower, lower.next_major())
+        }
+        _ => return Err("Expected a valid lax version spec".into()),
+    };
+    semver::Range::closed_open(lower, upper)
+        .map(semver::Constraint::from)
+        .ok_or_else(|| "Expected a valid lax version spec".into())
 }
 
+const ZERO_MAJOR_LAX_VERSION: &str =
+    "@0 would match any pre-1.0 version, where minor bumps may break things. Please pick a minor version too, e.g. @0.19";
+
 pub fn valid_package_name(name: String) -> std::result::Result<(), String> {
     let name: std::result::Result<package::Name, _> = name.parse();
     name.map(|_| ()).map_err(|e| e.to_string())
@@ -121,11 +358,26 @@ pub fn valid_lax_version(version: String) -> std::result::Result<(), String> {
         .collect::<std::result::Result<Vec<_>, _>>()
         .map_err(|e| anyhow!("{}", e).to_string())?;
     match parts.as_slice() {
-        [_] => Ok(()),
+        [0] => Err(ZERO_MAJOR_LAX_VERSION.into()),
+        [_] | [_, _] => Ok(()),
         _ => Err(anyhow!("Invalid lax version: {}", version).to_string()),
     }
 }
 
+/// Like `valid_package`, but also accepts a project name without an author, e.g. `http@2`.
+pub fn valid_package_shorthand(pkg: String) -> std::result::Result<(), String> {
+    match pkg.split_once('@') {
+        Some((project, version)) if !project.contains('/') => {
+            package::Name::validate_project(project).map_err(|e| e.to_string())?;
+            valid_version(version.to_string()).or_else(|_| valid_lax_version(version.to_string()))
+        }
+        None if !pkg.contains('/') => {
+            package::Name::validate_project(&pkg).map_err(|e| e.to_string())
+        }
+        _ => valid_package(pkg),
+    }
+}
+
 pub fn valid_package(pkg: String) -> std::result::Result<(), String> {
     let parts: Vec<&str> = pkg.split('@').collect();
     match parts.as_slice() {
@@ -134,10 +386,311 @@ pub fn valid_package(pkg: String) -> std::result::Result<(), String> {
             valid_version((*version).to_string())
                 .or_else(|_| valid_lax_version((*version).to_string()))
         }),
-        _ => unreachable!(),
+        _ => Err("Expected a package name, optionally followed by @ and a version".into()),
+    }
+}
+
+pub fn valid_exact_package(dep: String) -> std::result::Result<(), String> {
+    match dep.split_once('@') {
+        Some((name, version)) => {
+            valid_package_name(name.to_string()).and_then(|_| valid_version(version.to_string()))
+        }
+        None => Err("Expected a package name followed by @ and an exact version".into()),
+    }
+}
+
+/// The most spaces elm.json files may be indented with, whether set by flag or in elm-json.toml.
+pub const MAX_INDENT: usize = 16;
+
+fn parse_indent(indent: &str) -> std::result::Result<usize, String> {
+    match indent.parse::<usize>() {
+        Ok(n) if n <= MAX_INDENT => Ok(n),
+        Ok(_) => Err(format!("Indentation can be at most {} spaces", MAX_INDENT)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn valid_indent(indent: String) -> std::result::Result<(), String> {
+    parse_indent(&indent).map(|_| ())
+}
+
+pub fn valid_count(count: String) -> std::result::Result<(), String> {
+    count
+        .parse::<usize>()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Parse a duration like `90s`, `30m`, `12h` or `7d`.
+pub fn parse_duration(duration: &str) -> std::result::Result<Duration, String> {
+    let split = duration
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(duration.len());
+    let (amount, unit) = duration.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("Expected a duration like 12h or 7d, got {}", duration))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "Expected the unit of {} to be one of s, m, h or d",
+                duration
+            ))
+        }
+    };
+    amount
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("{} is too long a duration", duration))
+}
+
+pub fn valid_duration(duration: String) -> std::result::Result<(), String> {
+    parse_duration(&duration).map(|_| ())
+}
+
+/// A package name, possibly with `*` wildcards, optionally followed by `@` and an exact version.
+pub fn valid_package_pattern(pattern: String) -> std::result::Result<(), String> {
+    let pattern = match pattern.split_once('@') {
+        Some((pattern, version)) => {
+            valid_version(version.to_string())?;
+            pattern.to_string()
+        }
+        None => pattern,
+    };
+    if pattern.contains('*') {
+        Ok(())
+    } else {
+        valid_package_name(pattern)
+    }
+}
+
+pub fn check_licenses(
+    matches: &ArgMatches,
+    root_license: Option<&str>,
+    retriever: &Retriever,
+    g: &solver::Graph<solver::Summary<PackageId>>,
+) {
+    if !matches.is_present("check-licenses") {
+        return;
+    }
+
+    // Copyleft projects may depend on whatever they like.
+    if matches!(root_license, Some(l) if package::is_copyleft_license(l)) {
+        return;
+    }
+
+    let allowed = matches.values_of_lossy("allow-license").unwrap_or_default();
+    let flagged: Vec<_> = g
+        .raw_nodes()
+        .iter()
+        .map(|node| &node.weight)
+        .filter_map(|pkg| match (&pkg.id, retriever.license(pkg)) {
+            (PackageId::Pkg(name), Some(license))
+                if package::is_copyleft_license(license)
+                    && !allowed.iter().any(|a| a == license) =>
+            {
+                Some((name, pkg.version, license))
+            }
+            _ => None,
+        })
+        .collect();
+
+    if flagged.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "{} these dependencies use a copyleft license, which may not be compatible with {}:\n",
+        "Warning:".yellow(),
+        root_license.map_or_else(
+            || "your project".to_string(),
+            |l| format!("the {} license of your package", l)
+        )
+    );
+    for (name, version, license) in flagged {
+        eprintln!("- {} {} ({})", name, version, license);
+    }
+    eprintln!();
+}
+
+pub fn elm_version(g: &solver::Graph<solver::Summary<PackageId>>) -> Option<semver::Version> {
+    g.raw_nodes()
+        .iter()
+        .find(|node| node.weight.id == PackageId::Elm)
+        .map(|node| node.weight.version)
+}
+
+/// Report every dependency that doesn't exist at once, rather than letting the first one we run
+/// into surface as a resolution failure.
+fn check_known_packages(retriever: &Retriever) -> Result<()> {
+    // Without any known packages, e.g. when working offline without a cache, there's nothing to
+    // compare against.
+    if retriever.latest_versions().is_empty() {
+        return Ok(());
+    }
+
+    let unknown: Vec<String> = retriever
+        .root_packages()
+        .into_iter()
+        .filter(|name| !retriever.is_known(name))
+        .map(|name| format!("- {}", name))
+        .collect();
+
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "These packages don't exist, maybe there's a typo?\n\n{}",
+            unknown.join("\n")
+        ))
+        .context(Kind::InvalidElmJson)
     }
 }
 
+/// Run the resolver on the dependencies set up in `retriever`, after making sure they all exist.
+pub fn resolve(
+    options: &Options,
+    retriever: &mut Retriever,
+) -> Result<solver::Graph<solver::Summary<PackageId>>> {
+    check_known_packages(retriever)?;
+    let res = Resolver::new(retriever)
+        .with_timeout(options.solve_timeout)
+        .solve();
+    retriever.finish_progress();
+    res.network_context(Kind::NoResolution)
+}
+
+/// Solve the dependencies set up in `retriever`. With `--memoize`, the versions picked are
+/// remembered, keyed by everything that goes into the solve, and reused as long as that doesn't
+/// change and they still fit together.
+pub fn solve<T: Serialize>(
+    matches: &ArgMatches,
+    options: &Options,
+    project: &T,
+    retriever: &mut Retriever,
+) -> Result<solver::Graph<solver::Summary<PackageId>>> {
+    let key = if options.memoize {
+        Some(solution_key(matches, options, project, retriever)?)
+    } else {
+        None
+    };
+
+    if let Some(versions) = key.and_then(|key| retriever.memoized_solution(key)) {
+        let res = solver::build_graph(retriever, |pkg| versions.get(pkg).copied());
+        retriever.finish_progress();
+        if let Some(g) = res.network_context(Kind::NoResolution)? {
+            debug!("Reusing memoized solution");
+            return Ok(g);
+        }
+    }
+
+    let g = resolve(options, retriever)?;
+
+    if let Some(key) = key {
+        if let Err(e) = retriever.memoize_solution(key, &g) {
+            warn!("Failed to memoize solution: {}", e);
+        }
+    }
+    Ok(g)
+}
+
+fn solution_key<T: Serialize>(
+    matches: &ArgMatches,
+    options: &Options,
+    project: &T,
+    retriever: &Retriever,
+) -> Result<u64> {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(project)
+        .context(Kind::Unknown)?
+        .hash(&mut hasher);
+    retriever.known_versions().hash(&mut hasher);
+    options.offline.hash(&mut hasher);
+    options.prefer_cached.hash(&mut hasher);
+    matches.is_present("test").hash(&mut hasher);
+    matches.is_present("minimize").hash(&mut hasher);
+    matches
+        .values_of_lossy("minimize-package")
+        .hash(&mut hasher);
+    matches.is_present("no-elm").hash(&mut hasher);
+    matches.values_of_lossy("extra").hash(&mut hasher);
+    matches.values_of_lossy("prefer").hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+/// At `-v` and up, report how long solving took and how much had to be fetched to get there.
+pub fn report_timing(started: Instant, retriever: &Retriever) {
+    if !tracing::level_enabled!(Level::INFO) {
+        return;
+    }
+
+    let stats = retriever.stats();
+    eprintln!(
+        "Solved in {:.2?}: fetched {} new package versions, downloaded {} and read {} elm.json files",
+        started.elapsed(),
+        stats.versions_fetched,
+        stats.deps_fetched,
+        stats.deps_read
+    );
+}
+
 pub fn format_header(x: &str) -> String {
     format!("-- {} {}", x, "-".repeat(80 - 4 - x.len()))
 }
+
+/// Walk through the derivation of a failed resolution, one step at a time when someone is around to
+/// read along. Errors other than resolution failures are left alone.
+pub fn explain_conflict(e: &anyhow::Error) -> Result<()> {
+    let conflict = match e.downcast_ref::<solver::Conflict<PackageId>>() {
+        Some(conflict) => conflict,
+        None => return Ok(()),
+    };
+
+    eprintln!("{}\n", format_header("CONFLICT, STEP BY STEP").yellow());
+    let steps = conflict.steps();
+    let interactive = console::user_attended_stderr();
+
+    for (ix, step) in steps.iter().enumerate() {
+        eprintln!(
+            "{}\n",
+            textwrap::fill(&format!("({}) {}", ix + 1, step), 80)
+        );
+
+        if interactive
+            && ix + 1 < steps.len()
+            && !Confirm::new()
+                .with_prompt("Show the next step?")
+                .default(true)
+                .interact()
+                .context(Kind::Unknown)?
+        {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_duration("12h"), Ok(Duration::from_secs(12 * 60 * 60)));
+        assert_eq!(
+            parse_duration("7d"),
+            Ok(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+        assert!(parse_duration("7").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("1w").is_err());
+        assert!(parse_duration("999999999999999999d").is_err());
+    }
+}
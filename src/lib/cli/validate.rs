@@ -0,0 +1,38 @@
+use super::Kind;
+use crate::{
+    package::retriever::Options,
+    project::{self, validate},
+};
+use anyhow::{anyhow, Context, Result};
+use clap::ArgMatches;
+use std::fs;
+
+pub fn run(matches: &ArgMatches, _options: &Options) -> Result<()> {
+    let path = matches.value_of("INPUT").unwrap();
+
+    let contents = fs::read_to_string(path).context(Kind::MissingElmJson)?;
+    let contents = if matches.is_present("allow-comments") {
+        project::strip_comments(&contents)
+    } else {
+        contents
+    };
+
+    // Both modes apply the same checks; without `--all` only the first problem is reported.
+    let mut issues = validate::issues(&contents);
+    if !matches.is_present("all") {
+        issues.truncate(1);
+    }
+    if !issues.is_empty() {
+        let issues: Vec<_> = issues.iter().map(|issue| format!("- {}", issue)).collect();
+        return Err(anyhow!(
+            "I found {} problem(s) in {}:\n\n{}",
+            issues.len(),
+            path,
+            issues.join("\n")
+        ))
+        .context(Kind::InvalidElmJson);
+    }
+
+    println!("{} is valid.", path);
+    Ok(())
+}
@@ -23,7 +23,8 @@ impl fmt::Display for Kind {
     }
 }
 
-pub fn show<'a, L, R, K: 'a, T: 'a>(kind: Kind, left: L, right: R)
+/// Print the changes between `left` and `right`, if any, returning how many entries changed.
+pub fn show<'a, L, R, K: 'a, T: 'a>(kind: Kind, left: L, right: R) -> usize
 where
     L: IntoIterator<Item = (&'a K, &'a T)>,
     R: IntoIterator<Item = (&'a K, &'a T)>,
@@ -36,6 +37,7 @@ where
         it.print();
         println!();
     }
+    it.len()
 }
 
 impl<'a, K, T> Diff<'a, K, T>
@@ -102,6 +104,11 @@ where
         self.only_left.is_empty() && self.only_right.is_empty() && self.changed.is_empty()
     }
 
+    /// The number of entries that were added, removed or changed.
+    pub fn len(&self) -> usize {
+        self.only_left.len() + self.only_right.len() + self.changed.len()
+    }
+
     pub fn print(&self) {
         for (k, v) in &self.only_left {
             println!("- {} {} {}", "[DEL]".yellow(), k, v);
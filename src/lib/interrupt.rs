@@ -0,0 +1,31 @@
+//! Ctrl-C doesn't kill elm-json outright. Instead, the handler installed by the binary records that
+//! an interruption was requested, and long-running work checks for that at points where stopping
+//! is safe.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use thiserror::Error;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Work was stopped because the user pressed Ctrl-C.
+#[derive(Debug, Error)]
+#[error("I stopped because you pressed Ctrl-C. Nothing was written.")]
+pub struct Interrupted;
+
+/// Record that we've been asked to stop, returning whether that had already happened before.
+pub fn request() -> bool {
+    INTERRUPTED.swap(true, Ordering::SeqCst)
+}
+
+pub fn requested() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Fail with `Interrupted` if we've been asked to stop.
+pub fn check() -> Result<(), Interrupted> {
+    if requested() {
+        Err(Interrupted)
+    } else {
+        Ok(())
+    }
+}
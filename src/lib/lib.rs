@@ -1,5 +1,6 @@
 pub mod cli;
 pub mod diff;
+pub mod interrupt;
 pub mod package;
 pub mod project;
 pub mod semver;
@@ -1,5 +1,6 @@
 use crate::semver::{self, Version};
 use anyhow::{anyhow, bail, Error};
+use indexmap::IndexMap;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 use std::collections::BTreeMap;
@@ -19,7 +20,9 @@ pub struct Package {
     version: Version,
     exposed_modules: Exposed,
     elm_version: Range,
+    #[serde(deserialize_with = "deserialize_dependencies")]
     pub dependencies: BTreeMap<Name, Range>,
+    #[serde(deserialize_with = "deserialize_dependencies")]
     pub test_dependencies: BTreeMap<Name, Range>,
     #[serde(flatten)]
     other: BTreeMap<String, Value>,
@@ -42,6 +45,20 @@ impl Name {
         })
     }
 
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    pub fn project(&self) -> &str {
+        &self.project
+    }
+
+    /// Whether both names refer to the same package once GitHub's case-insensitive usernames
+    /// are taken into account. Project names are always lowercase, so only the author can differ.
+    pub fn eq_ignore_case(&self, other: &Self) -> bool {
+        self.project == other.project && self.author.eq_ignore_ascii_case(&other.author)
+    }
+
     fn validate_author(author: &str) -> Result<(), Error> {
         if author.is_empty() {
             bail!(
@@ -77,7 +94,7 @@ impl Name {
         Ok(())
     }
 
-    fn validate_project(project: &str) -> Result<(), Error> {
+    pub fn validate_project(project: &str) -> Result<(), Error> {
         if project.is_empty() {
             bail!(
                 "Project name maybe not be empty. A valid package name looks like \"author/project\"."
@@ -141,11 +158,127 @@ impl<'de> Deserialize<'de> for Name {
     }
 }
 
+/// Deserialize a map of dependencies, rejecting packages that are listed more than once rather
+/// than silently keeping the last entry. Names that only differ in the case of the author count
+/// as the same package too.
+pub fn deserialize_dependencies<'de, D, V>(deserializer: D) -> Result<BTreeMap<Name, V>, D::Error>
+where
+    D: Deserializer<'de>,
+    V: Deserialize<'de>,
+{
+    struct DependenciesVisitor<V>(std::marker::PhantomData<V>);
+
+    impl<'de, V: Deserialize<'de>> de::Visitor<'de> for DependenciesVisitor<V> {
+        type Value = BTreeMap<Name, V>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map of package names to versions")
+        }
+
+        fn visit_map<M: de::MapAccess<'de>>(self, mut access: M) -> Result<Self::Value, M::Error> {
+            let mut deps = BTreeMap::new();
+            while let Some((name, value)) = access.next_entry::<Name, V>()? {
+                if deps.contains_key(&name) {
+                    return Err(de::Error::custom(format!(
+                        "{} is listed more than once in the same dependencies",
+                        name
+                    )));
+                }
+                if let Some(other) = deps.keys().find(|other| name.eq_ignore_case(other)) {
+                    return Err(de::Error::custom(format!(
+                        "{} and {} only differ in the case of the author, so they're the same package listed twice",
+                        other, name
+                    )));
+                }
+                deps.insert(name, value);
+            }
+            Ok(deps)
+        }
+    }
+
+    deserializer.deserialize_map(DependenciesVisitor(std::marker::PhantomData))
+}
+
+/// The order in which dependencies are written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DependencyOrder {
+    /// By author, then project, the way the Elm compiler writes them.
+    Canonical,
+    /// Like `Canonical`, but ignoring case. Doesn't apply to the indirect dependencies of an
+    /// application, which are only ever touched by tools and always use `Canonical`.
+    IgnoreCase,
+}
+
+/// Dependencies along with the order to write them in.
+pub struct OrderedDependencies<'a, V> {
+    deps: &'a BTreeMap<Name, V>,
+    order: DependencyOrder,
+}
+
+impl<'a, V> OrderedDependencies<'a, V> {
+    pub fn new(deps: &'a BTreeMap<Name, V>, order: DependencyOrder) -> Self {
+        Self { deps, order }
+    }
+}
+
+impl<V: Serialize> Serialize for OrderedDependencies<'_, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.order {
+            // The order of `Name` matches how the Elm compiler sorts package names, so this is
+            // exactly what `elm install` writes.
+            DependencyOrder::Canonical => serializer.collect_map(self.deps),
+            DependencyOrder::IgnoreCase => {
+                let mut sorted: Vec<_> = self.deps.iter().collect();
+                // The sort is stable, so names only differing in case stay in canonical order.
+                sorted.sort_by_cached_key(|(name, _)| {
+                    (name.author.to_lowercase(), name.project.to_lowercase())
+                });
+                serializer.collect_map(sorted)
+            }
+        }
+    }
+}
+
+/// A package borrowed for writing, with its dependencies in a particular order.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct OrderedPackage<'a> {
+    name: &'a Name,
+    summary: &'a str,
+    license: &'a str,
+    version: &'a Version,
+    exposed_modules: &'a Exposed,
+    elm_version: &'a Range,
+    dependencies: OrderedDependencies<'a, Range>,
+    test_dependencies: OrderedDependencies<'a, Range>,
+    #[serde(flatten)]
+    other: &'a BTreeMap<String, Value>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 pub enum Exposed {
     Plain(Vec<String>),
-    Structured(BTreeMap<String, Vec<String>>),
+    /// Modules grouped under headings, which keep the order they were written in since that's
+    /// the order they're shown in on package.elm-lang.org.
+    Structured(IndexMap<String, Vec<String>>),
+}
+
+impl Exposed {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Exposed::Plain(modules) => modules.is_empty(),
+            Exposed::Structured(groups) => groups.values().all(Vec::is_empty),
+        }
+    }
+
+    /// Every exposed module, regardless of how they're grouped.
+    pub fn modules(&self) -> Vec<&String> {
+        match self {
+            Exposed::Plain(modules) => modules.iter().collect(),
+            Exposed::Structured(groups) => groups.values().flatten().collect(),
+        }
+    }
 }
 
 impl Package {
@@ -173,6 +306,49 @@ impl Package {
         self.elm_version
     }
 
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    pub fn with_name(self, name: Name) -> Self {
+        Self { name, ..self }
+    }
+
+    pub fn with_version(self, version: Version) -> Self {
+        Self { version, ..self }
+    }
+
+    /// Borrow the package for writing, with its dependencies in the given order.
+    pub fn ordered(&self, order: DependencyOrder) -> OrderedPackage<'_> {
+        OrderedPackage {
+            name: &self.name,
+            summary: &self.summary,
+            license: &self.license,
+            version: &self.version,
+            exposed_modules: &self.exposed_modules,
+            elm_version: &self.elm_version,
+            dependencies: OrderedDependencies::new(&self.dependencies, order),
+            test_dependencies: OrderedDependencies::new(&self.test_dependencies, order),
+            other: &self.other,
+        }
+    }
+
+    pub fn license(&self) -> &str {
+        &self.license
+    }
+
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    pub fn exposed_modules(&self) -> &Exposed {
+        &self.exposed_modules
+    }
+
     pub fn with_deps(
         &self,
         dependencies: BTreeMap<Name, Range>,
@@ -199,28 +375,183 @@ impl Package {
     }
 
     pub fn all_dependencies(&self) -> Result<Vec<(Name, semver::Range)>, Error> {
-        let mut all_deps: BTreeMap<Name, Range> = self.dependencies.clone();
-
-        for (k, v) in &self.test_dependencies {
-            if let Some(e) = all_deps.get(k) {
-                bail!(
-                    "Dependency {}@{} duplicated in test-dependencies as {}",
-                    k,
-                    e,
-                    v
-                )
-            }
-
-            all_deps.insert(k.clone(), *v);
+        // Compare against the original maps rather than the merged one, so the outcome doesn't
+        // depend on the order in which we go through them.
+        if let Some((name, test_range)) = self
+            .test_dependencies
+            .iter()
+            .find(|(name, _)| self.dependencies.contains_key(*name))
+        {
+            bail!(
+                "{} appears in both dependencies ({}) and test-dependencies ({}); a package may only be in one.",
+                name,
+                self.dependencies[name],
+                test_range
+            )
         }
 
-        Ok(all_deps
+        let all_deps: BTreeMap<&Name, &Range> = self
+            .dependencies
             .iter()
+            .chain(self.test_dependencies.iter())
+            .collect();
+
+        Ok(all_deps
+            .into_iter()
             .map(|(k, v)| (k.clone(), v.to_constraint_range()))
             .collect())
     }
 }
 
+/// Check that a package summary is short enough to be published.
+pub fn validate_summary(summary: &str) -> Result<(), Error> {
+    if summary.len() > 80 {
+        bail!("Summary may not be over 80 characters long.")
+    }
+
+    Ok(())
+}
+
+/// Whether an SPDX license identifier is one of the OSI approved licenses package.elm-lang.org
+/// accepts.
+pub fn is_approved_license(license: &str) -> bool {
+    APPROVED_LICENSES.contains(&license)
+}
+
+/// Whether an SPDX license identifier is a (strong or weak) copyleft license.
+pub fn is_copyleft_license(license: &str) -> bool {
+    COPYLEFT_LICENSES.contains(&license)
+}
+
+const APPROVED_LICENSES: &[&str] = &[
+    "AFL-1.1",
+    "AFL-1.2",
+    "AFL-2.0",
+    "AFL-2.1",
+    "AFL-3.0",
+    "APL-1.0",
+    "Apache-1.1",
+    "Apache-2.0",
+    "APSL-1.0",
+    "APSL-1.1",
+    "APSL-1.2",
+    "APSL-2.0",
+    "Artistic-1.0",
+    "Artistic-1.0-Perl",
+    "Artistic-1.0-cl8",
+    "Artistic-2.0",
+    "AAL",
+    "BSL-1.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "0BSD",
+    "CECILL-2.1",
+    "CNRI-Python",
+    "CDDL-1.0",
+    "CPAL-1.0",
+    "CPL-1.0",
+    "CATOSL-1.1",
+    "CUA-OPL-1.0",
+    "EPL-1.0",
+    "ECL-1.0",
+    "ECL-2.0",
+    "EFL-1.0",
+    "EFL-2.0",
+    "Entessa",
+    "EUDatagrid",
+    "EUPL-1.1",
+    "Fair",
+    "Frameworx-1.0",
+    "AGPL-3.0",
+    "GPL-2.0",
+    "GPL-3.0",
+    "LGPL-2.1",
+    "LGPL-3.0",
+    "LGPL-2.0",
+    "HPND",
+    "IPL-1.0",
+    "Intel",
+    "IPA",
+    "ISC",
+    "LPPL-1.3c",
+    "LiLiQ-P-1.1",
+    "LiLiQ-Rplus-1.1",
+    "LiLiQ-R-1.1",
+    "LPL-1.02",
+    "LPL-1.0",
+    "MS-PL",
+    "MS-RL",
+    "MirOS",
+    "MIT",
+    "Motosoto",
+    "MPL-1.0",
+    "MPL-1.1",
+    "MPL-2.0",
+    "MPL-2.0-no-copyleft-exception",
+    "Multics",
+    "NASA-1.3",
+    "Naumen",
+    "NGPL",
+    "Nokia",
+    "NPOSL-3.0",
+    "NTP",
+    "OCLC-2.0",
+    "OGTSL",
+    "OSL-1.0",
+    "OSL-2.0",
+    "OSL-2.1",
+    "OSL-3.0",
+    "OSET-PL-2.1",
+    "PHP-3.0",
+    "PostgreSQL",
+    "Python-2.0",
+    "QPL-1.0",
+    "RPSL-1.0",
+    "RPL-1.1",
+    "RPL-1.5",
+    "RSCPL",
+    "OFL-1.1",
+    "SimPL-2.0",
+    "Sleepycat",
+    "SISSL",
+    "SPL-1.0",
+    "Watcom-1.0",
+    "UPL-1.0",
+    "NCSA",
+    "VSL-1.0",
+    "W3C",
+    "Xnet",
+    "Zlib",
+    "ZPL-2.0",
+];
+
+const COPYLEFT_LICENSES: &[&str] = &[
+    "AGPL-3.0",
+    "CDDL-1.0",
+    "CECILL-2.1",
+    "CPL-1.0",
+    "EPL-1.0",
+    "EUPL-1.1",
+    "GPL-2.0",
+    "GPL-3.0",
+    "IPL-1.0",
+    "LGPL-2.0",
+    "LGPL-2.1",
+    "LGPL-3.0",
+    "MPL-1.0",
+    "MPL-1.1",
+    "MPL-2.0",
+    "MS-RL",
+    "OSL-1.0",
+    "OSL-2.0",
+    "OSL-2.1",
+    "OSL-3.0",
+    "RPL-1.1",
+    "RPL-1.5",
+    "Sleepycat",
+    "SPL-1.0",
+];
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Range {
     lower: Version,
@@ -237,6 +568,11 @@ impl Range {
         }
     }
 
+    /// A range which only allows the given version, expressed as `x.y.z <= v < x.y.(z+1)`.
+    pub fn exact(v: Version) -> Self {
+        Range::new(v, v.next_patch(), false)
+    }
+
     pub fn to_constraint(&self) -> semver::Constraint {
         self.to_constraint_range().into()
     }
@@ -275,7 +611,7 @@ impl str::FromStr for Range {
 
 impl From<Version> for Range {
     fn from(v: Version) -> Self {
-        Range::new(v, Version::new(v.major() + 1, 0, 0), false)
+        Range::new(v, v.next_major(), false)
     }
 }
 
@@ -322,4 +658,108 @@ mod tests {
         assert!("foo-/bar".parse::<Name>().is_err());
         assert!("foo/ba-".parse::<Name>().is_err());
     }
+
+    #[test]
+    fn test_package_metadata() {
+        assert!(validate_summary("helpful summary of your project").is_ok());
+        assert!(validate_summary(&"x".repeat(81)).is_err());
+
+        assert!(is_approved_license("BSD-3-Clause"));
+        assert!(!is_approved_license("WTFPL"));
+    }
+
+    #[test]
+    fn test_range_upper_bounds() {
+        let v = Version::new(1, 2, 3);
+        assert_eq!(Range::from(v).to_string(), "1.2.3 <= v < 2.0.0");
+        assert_eq!(Range::exact(v).to_string(), "1.2.3 <= v < 1.2.4");
+
+        // The safe range for an application's version ends where the package range does.
+        assert_eq!(
+            Range::from(v).to_constraint_range(),
+            semver::Range::from(&v, &semver::Strictness::Safe)
+        );
+    }
+
+    #[test]
+    fn test_duplicate_dependencies() {
+        #[derive(Deserialize)]
+        struct Deps(#[serde(deserialize_with = "deserialize_dependencies")] BTreeMap<Name, Range>);
+
+        let unique = r#"{"elm/core": "1.0.0 <= v < 2.0.0", "elm/json": "1.0.0 <= v < 2.0.0"}"#;
+        assert_eq!(serde_json::from_str::<Deps>(unique).unwrap().0.len(), 2);
+
+        let duplicate = r#"{"elm/core": "1.0.0 <= v < 2.0.0", "elm/core": "1.0.2 <= v < 2.0.0"}"#;
+        let err = serde_json::from_str::<Deps>(duplicate).err().unwrap();
+        assert!(err
+            .to_string()
+            .contains("elm/core is listed more than once"));
+
+        let case_variant =
+            r#"{"User/pkg": "1.0.0 <= v < 2.0.0", "user/pkg": "1.0.0 <= v < 2.0.0"}"#;
+        let err = serde_json::from_str::<Deps>(case_variant).err().unwrap();
+        assert!(err
+            .to_string()
+            .contains("User/pkg and user/pkg only differ in the case of the author"));
+    }
+
+    #[test]
+    fn test_dependency_in_both_sections() {
+        let mut package = Package::new(
+            "author/project".parse().unwrap(),
+            String::new(),
+            "BSD-3-Clause".to_string(),
+        );
+        package.test_dependencies.insert(
+            "elm/core".parse().unwrap(),
+            Range::new(Version::new(1, 0, 2), Version::new(2, 0, 0), false),
+        );
+
+        let err = package.all_dependencies().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "elm/core appears in both dependencies (1.0.0 <= v < 2.0.0) and test-dependencies (1.0.2 <= v < 2.0.0); a package may only be in one."
+        );
+    }
+
+    #[test]
+    fn test_dependency_order() {
+        let deps: BTreeMap<Name, Version> =
+            ["Zwilias/elm-json", "elm/core", "elm-community/list-extra"]
+                .iter()
+                .map(|name| (name.parse().unwrap(), Version::new(1, 0, 0)))
+                .collect();
+        let names = |order| -> Vec<String> {
+            let json = serde_json::to_string(&OrderedDependencies::new(&deps, order)).unwrap();
+            let map: BTreeMap<String, Value> = serde_json::from_str(&json).unwrap();
+            let mut names: Vec<_> = map.keys().cloned().collect();
+            names.sort_by_key(|name| json.find(name.as_str()));
+            names
+        };
+
+        assert_eq!(
+            names(DependencyOrder::Canonical),
+            ["Zwilias/elm-json", "elm/core", "elm-community/list-extra"]
+        );
+        assert_eq!(
+            names(DependencyOrder::IgnoreCase),
+            ["elm/core", "elm-community/list-extra", "Zwilias/elm-json"]
+        );
+    }
+
+    #[test]
+    fn test_exposed_modules() {
+        let plain: Exposed = serde_json::from_str(r#"["Json.Decode", "Json.Encode"]"#).unwrap();
+        assert_eq!(plain.modules(), ["Json.Decode", "Json.Encode"]);
+
+        let json = r#"{"Primitives":["String","Char"],"Collections":["List","Array"]}"#;
+        let structured: Exposed = serde_json::from_str(json).unwrap();
+        assert_eq!(structured.modules(), ["String", "Char", "List", "Array"]);
+        assert_eq!(serde_json::to_string(&structured).unwrap(), json);
+        assert!(!plain.is_empty() && !structured.is_empty());
+
+        let empty: Exposed = serde_json::from_str(r#"{"Primitives":[]}"#).unwrap();
+        assert!(empty.is_empty());
+        assert!(Exposed::Plain(Vec::new()).is_empty());
+    }
 }
@@ -1,26 +1,136 @@
 use crate::{
     package,
     semver::{Constraint, Range, Version},
-    solver::{incompat::Incompatibility, retriever, summary},
+    solver::{
+        self,
+        incompat::{Incompatibility, IncompatibilityCause},
+        retriever, summary,
+    },
 };
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use console::Term;
 use fs2::FileExt;
+use indexmap::indexmap;
 use serde::ser::Serialize;
 use std::{
-    collections::HashMap,
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     env, fmt,
     fs::{self, DirBuilder, File, OpenOptions},
-    io::{BufReader, BufWriter},
-    path::PathBuf,
+    io::{BufReader, BufWriter, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
 };
 use tracing::{debug, warn};
 
+const CACHE_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const CACHE_LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+/// Even large applications depend on well under this many packages, so a solve looking at more
+/// usually means a misconfigured registry or a constraint that pulls in far too much.
+const CONSIDERED_PACKAGES_WARNING: usize = 500;
+
 pub struct Retriever {
     deps_cache: HashMap<Summary, Vec<Incompatibility<PackageId>>>,
     versions: HashMap<PackageId, Vec<Version>>,
     preferred_versions: HashMap<PackageId, Version>,
+    hinted_versions: HashMap<PackageId, Version>,
+    minimized: HashSet<PackageId>,
+    considered: HashSet<PackageId>,
+    licenses: HashMap<Summary, String>,
     mode: Mode,
-    offline: bool,
+    options: Options,
+    stats: Stats,
+    progress: Option<Progress>,
+    elm_home: PathBuf,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub offline: bool,
+    /// Never go over the network, and fail rather than continue when something isn't cached.
+    pub locked: bool,
+    pub registry_file: Option<PathBuf>,
+    pub prefer_cached: bool,
+    pub memoize: bool,
+    /// Rebuild the cached list of package versions from scratch.
+    pub refresh: bool,
+    /// Rebuild the cached list of package versions when it's older than this.
+    pub max_age: Option<Duration>,
+    /// Show a spinner on stderr while downloading, if that's a terminal.
+    pub progress: bool,
+    /// Give up on solving after this long.
+    pub solve_timeout: Option<Duration>,
+}
+
+/// Every Elm release packages can be written for, oldest first.
+pub fn known_elm_versions() -> Vec<Version> {
+    vec![
+        Version::new(0, 14, 0),
+        Version::new(0, 15, 0),
+        Version::new(0, 16, 0),
+        Version::new(0, 17, 0),
+        Version::new(0, 18, 0),
+        Version::new(0, 19, 0),
+        Version::new(0, 19, 1),
+    ]
+}
+
+/// A spinner on stderr, telling what we're downloading.
+struct Progress {
+    term: Term,
+    ticks: Cell<usize>,
+}
+
+impl Progress {
+    const FRAMES: &'static [char] = &['|', '/', '-', '\\'];
+
+    fn new() -> Option<Self> {
+        let term = Term::stderr();
+        if term.is_term() {
+            Some(Self {
+                term,
+                ticks: Cell::new(0),
+            })
+        } else {
+            None
+        }
+    }
+
+    fn tick(&self, message: &str) {
+        let ticks = self.ticks.get();
+        self.ticks.set(ticks + 1);
+        let _ = self.term.clear_line();
+        let _ = self.term.write_str(&format!(
+            "{} {}",
+            Self::FRAMES[ticks % Self::FRAMES.len()],
+            message
+        ));
+    }
+
+    fn finish(&self) {
+        if self.ticks.get() > 0 {
+            let _ = self.term.clear_line();
+            self.ticks.set(0);
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Counters describing how much work was needed to gather package information.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stats {
+    /// Package versions newly fetched from package.elm-lang.org.
+    pub versions_fetched: usize,
+    /// elm.json files downloaded from package.elm-lang.org.
+    pub deps_fetched: usize,
+    /// elm.json files read from `ELM_HOME` or our own cache.
+    pub deps_read: usize,
 }
 
 type Summary = summary::Summary<PackageId>;
@@ -70,33 +180,86 @@ impl PackageId {
 }
 
 impl Retriever {
-    pub fn new(elm_version: &Constraint, offline: bool) -> Result<Self> {
-        let mut deps_cache = HashMap::new();
+    pub fn new(elm_version: &Constraint, options: &Options) -> Result<Self> {
+        Self::with_elm_home(elm_version, options, Self::elm_home()?)
+    }
 
-        deps_cache.insert(
-            Self::root(),
-            vec![Incompatibility::from_dep(
-                Self::root(),
-                (PackageId::Elm, elm_version.complement()),
-            )],
-        );
+    /// Like `new`, but reading and caching packages in `elm_home` rather than `ELM_HOME`.
+    pub(crate) fn with_elm_home(
+        elm_version: &Constraint,
+        options: &Options,
+        elm_home: PathBuf,
+    ) -> Result<Self> {
+        let mut deps_cache = HashMap::new();
+        deps_cache.insert(Self::root(), Self::root_deps(elm_version));
 
         let mut retriever = Self {
             deps_cache,
             versions: HashMap::new(),
             preferred_versions: HashMap::new(),
+            hinted_versions: HashMap::new(),
+            minimized: HashSet::new(),
+            considered: HashSet::new(),
+            licenses: HashMap::new(),
             mode: Mode::Maximize,
-            offline,
+            options: options.clone(),
+            stats: Stats::default(),
+            progress: if options.progress {
+                Progress::new()
+            } else {
+                None
+            },
+            elm_home,
         };
 
         retriever.fetch_versions()?;
         Ok(retriever)
     }
 
+    /// Forget the root dependencies and preferences of a previous solve, keeping everything we
+    /// know about the available packages so it can be reused for another project.
+    pub fn reset(&mut self, elm_version: &Constraint) {
+        self.deps_cache
+            .insert(Self::root(), Self::root_deps(elm_version));
+        self.preferred_versions.clear();
+        self.hinted_versions.clear();
+        self.minimized.clear();
+        self.considered.clear();
+        self.mode = Mode::Maximize;
+    }
+
+    fn root_deps(elm_version: &Constraint) -> Vec<Incompatibility<PackageId>> {
+        vec![Incompatibility::from_dep(
+            Self::root(),
+            (PackageId::Elm, elm_version.complement()),
+        )]
+    }
+
+    /// Switch offline mode on or off for the rest of this retriever's life. Everything already
+    /// fetched stays available either way; going offline only refuses new downloads. A `locked`
+    /// retriever never goes online, and coming back online picks up newly published versions.
+    pub fn set_offline(&mut self, offline: bool) -> Result<()> {
+        let was_offline = self.options.offline;
+        self.options.offline = offline || self.options.locked;
+        if was_offline && !self.options.offline {
+            self.fetch_versions()?;
+        }
+        Ok(())
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.options.offline
+    }
+
     pub fn minimize(&mut self) {
         self.mode = Mode::Minimize;
     }
 
+    /// Choose the lowest available version for this package only, whatever the overall mode.
+    pub fn minimize_package(&mut self, pkg: package::Name) {
+        self.minimized.insert(pkg.into());
+    }
+
     pub fn add_deps<'a, I>(&mut self, deps: I)
     where
         I: IntoIterator<Item = &'a (package::Name, Range)>,
@@ -108,6 +271,20 @@ impl Retriever {
         }));
     }
 
+    /// Every package added with `add_deps` or `add_dep` since the last `reset`.
+    pub fn root_packages(&self) -> BTreeSet<package::Name> {
+        self.deps_cache
+            .get(&Self::root())
+            .into_iter()
+            .flatten()
+            .flat_map(|incompat| incompat.deps().keys())
+            .filter_map(|id| match id {
+                PackageId::Pkg(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn add_dep(&mut self, name: package::Name, version: Option<Constraint>) {
         let constraint = version.map_or_else(Constraint::empty, |x| x.complement());
         let deps = self.deps_cache.entry(Self::root()).or_insert_with(Vec::new);
@@ -126,21 +303,75 @@ impl Retriever {
     }
 
     fn fetch_versions(&mut self) -> Result<()> {
-        let file = Self::cache_file()?;
-        file.lock_exclusive()?;
+        let mut versions = match &self.options.registry_file {
+            Some(path) => Self::read_registry_file(path)?,
+            None => self.fetch_cached_and_remote_versions()?,
+        };
+        dedup_versions(&mut versions);
+
+        let mut versions: HashMap<PackageId, Vec<Version>> = versions
+            .iter()
+            .map(|(k, v)| (k.clone().into(), v.clone()))
+            .collect();
+
+        versions.insert(PackageId::Root, vec![Version::new(1, 0, 0)]);
+        versions.insert(PackageId::Elm, known_elm_versions());
+
+        self.versions = versions;
+        Ok(())
+    }
+
+    fn fetch_cached_and_remote_versions(&mut self) -> Result<HashMap<package::Name, Vec<Version>>> {
+        // When ELM_HOME can't be written to, we can still read whatever is cached there, we just
+        // can't add to it.
+        let (file, writable) = match self.cache_file() {
+            Ok(file) => (file, true),
+            Err(e) => {
+                warn!("{} Continuing without updating the cache.", e);
+                match File::open(self.cache_file_path()) {
+                    Ok(file) => (file, false),
+                    Err(_) => return self.fetch_uncached_versions(),
+                }
+            }
+        };
+        Self::lock_cache_file(&file)?;
 
         let mut versions: HashMap<_, _> = self.fetch_cached_versions(&file).unwrap_or_default();
 
-        if !self.offline {
-            let count = Self::count_versions(&versions);
+        if self.options.locked && versions.is_empty() {
+            file.unlock()?;
+            self.would_fetch("the list of available packages")?;
+        }
 
-            let remote_versions = self.fetch_remote_versions(count).unwrap_or_else(|_| {
-                warn!("Failed to fetch versions from package.elm-lang.org");
-                HashMap::new()
-            });
+        if !self.options.offline {
+            let refresh = self.needs_refresh(&file);
+            let count = if refresh {
+                debug!("Rebuilding the cached versions from scratch");
+                0
+            } else {
+                Self::count_versions(&versions)
+            };
+
+            // Without anything cached, there's nothing to fall back on.
+            let remote_versions = match self.fetch_remote_versions(count) {
+                Ok(remote_versions) => remote_versions,
+                Err(e) if versions.is_empty() => {
+                    file.unlock()?;
+                    return Err(e);
+                }
+                Err(_) => {
+                    warn!("Failed to fetch versions from package.elm-lang.org");
+                    HashMap::new()
+                }
+            };
+            self.stats.versions_fetched = Self::count_versions(&remote_versions);
 
             let mut changed = false;
 
+            if refresh && !remote_versions.is_empty() {
+                versions.clear();
+            }
+
             for (pkg, vs) in &remote_versions {
                 let entry = versions.entry(pkg.clone()).or_insert_with(Vec::new);
                 entry.extend(vs);
@@ -148,33 +379,76 @@ impl Retriever {
             }
 
             if changed {
-                self.save_cached_versions(&file, &versions)?;
+                dedup_versions(&mut versions);
+                if writable {
+                    if let Err(e) = self.save_cached_versions(&file, &versions) {
+                        warn!("Failed to save the cached versions: {}", e);
+                    }
+                }
             }
         }
 
         file.unlock()?;
+        Ok(versions)
+    }
 
-        let mut versions: HashMap<PackageId, Vec<Version>> = versions
-            .iter()
-            .map(|(k, v)| (k.clone().into(), v.clone()))
-            .collect();
+    /// Without any cache to start from, all versions have to come from package.elm-lang.org.
+    fn fetch_uncached_versions(&mut self) -> Result<HashMap<package::Name, Vec<Version>>> {
+        if self.options.offline {
+            self.would_fetch("the list of available packages")?;
+            bail!("I need the list of available packages, but there's no cache to read it from and I'm working in offline mode!");
+        }
 
-        versions.insert(PackageId::Root, vec![Version::new(1, 0, 0)]);
-        versions.insert(
-            PackageId::Elm,
-            vec![
-                Version::new(0, 14, 0),
-                Version::new(0, 15, 0),
-                Version::new(0, 16, 0),
-                Version::new(0, 17, 0),
-                Version::new(0, 18, 0),
-                Version::new(0, 19, 0),
-                Version::new(0, 19, 1),
-            ],
-        );
+        let versions = self.fetch_remote_versions(0)?;
+        self.stats.versions_fetched = Self::count_versions(&versions);
+        Ok(versions)
+    }
 
-        self.versions = versions;
-        Ok(())
+    /// Whether the cached versions should be thrown away, either because we were asked to or
+    /// because they're older than `--max-age`.
+    fn needs_refresh(&self, file: &File) -> bool {
+        self.options.refresh
+            || self.options.max_age.is_some_and(|max_age| {
+                file.metadata()
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .and_then(|modified| modified.elapsed().ok())
+                    .is_some_and(|age| age > max_age)
+            })
+    }
+
+    fn lock_cache_file(file: &File) -> Result<()> {
+        let started = Instant::now();
+
+        loop {
+            match file.try_lock_exclusive() {
+                Ok(()) => return Ok(()),
+                Err(e) if e.kind() == fs2::lock_contended_error().kind() => {
+                    if started.elapsed() >= CACHE_LOCK_TIMEOUT {
+                        bail!(
+                            "Another elm-json process is using the cache and didn't release it within {} seconds. If no other elm-json process is running, try again.",
+                            CACHE_LOCK_TIMEOUT.as_secs()
+                        );
+                    }
+                    debug!("Cache file is locked by another process, waiting");
+                    thread::sleep(CACHE_LOCK_RETRY_INTERVAL);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn read_registry_file(path: &Path) -> Result<HashMap<package::Name, Vec<Version>>> {
+        debug!("Reading versions from {}", path.display());
+
+        let file = File::open(path).map_err(|_| {
+            anyhow!(
+                "I couldn't open the registry file at {}!",
+                path.to_string_lossy()
+            )
+        })?;
+        let entries: Vec<String> = serde_json::from_reader(BufReader::new(file))?;
+        Self::parse_registry_entries(&entries)
     }
 
     fn fetch_cached_versions(
@@ -186,17 +460,25 @@ impl Retriever {
         Ok(versions)
     }
 
-    fn cache_file() -> Result<File> {
-        let mut p_path = Self::packages_path()?;
-        p_path.push("elm-json");
-        fs::create_dir_all(p_path.clone())?;
-        p_path.push("versions.dat");
+    fn cache_file_path(&self) -> PathBuf {
+        let mut p_path = self.elm_home.clone();
+        p_path.push("elm-json/versions.dat");
+        p_path
+    }
 
-        OpenOptions::new()
-            .write(true)
-            .read(true)
-            .create(true)
-            .open(p_path)
+    fn cache_file(&self) -> Result<File> {
+        let p_path = self.cache_file_path();
+
+        p_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                OpenOptions::new()
+                    .write(true)
+                    .read(true)
+                    .create(true)
+                    .open(p_path)
+            })
             .map_err(|_| {
                 anyhow!("I couldn't open or create the cache file where I cache version info!")
             })
@@ -207,6 +489,10 @@ impl Retriever {
         cache_file: &File,
         versions: &HashMap<package::Name, Vec<Version>>,
     ) -> Result<()> {
+        // Overwrite rather than append to what we read earlier.
+        let mut cache_file = cache_file;
+        cache_file.set_len(0)?;
+        cache_file.seek(SeekFrom::Start(0))?;
         let writer = BufWriter::new(cache_file);
         bincode::serialize_into(writer, &versions)?;
         Ok(())
@@ -214,19 +500,30 @@ impl Retriever {
 
     fn fetch_remote_versions(&self, from: usize) -> Result<HashMap<package::Name, Vec<Version>>> {
         debug!("Fetching versions since {}", from);
+        self.tick("Fetching the list of packages");
 
         let url = format!("https://package.elm-lang.org/all-packages/since/{}", from);
-        let response = isahc::get(url)?;
+        let response = isahc::get(url).context(
+            "I couldn't download the list of packages from package.elm-lang.org. Check your internet connection, or use --offline to only use packages that are already cached.",
+        )?;
 
         let versions: Vec<String> = serde_json::from_reader(response.into_body())?;
+        Self::parse_registry_entries(&versions)
+    }
+
+    fn parse_registry_entries(entries: &[String]) -> Result<HashMap<package::Name, Vec<Version>>> {
         let mut res: HashMap<package::Name, Vec<Version>> = HashMap::new();
 
-        for entry in &versions {
+        for entry in entries {
             let parts: Vec<_> = entry.split('@').collect();
             match parts.as_slice() {
                 [p, v] => {
-                    let name: package::Name = p.parse()?;
-                    let version: Version = v.parse()?;
+                    let name: package::Name = p
+                        .parse()
+                        .map_err(|e| anyhow!("Invalid registry entry {}: {}", entry, e))?;
+                    let version: Version = v.parse().map_err(|e| {
+                        anyhow!("Invalid registry entry {} for {}: {}", entry, name, e)
+                    })?;
                     let entry = res.entry(name).or_insert_with(Vec::new);
                     entry.push(version)
                 }
@@ -237,6 +534,107 @@ impl Retriever {
         Ok(res)
     }
 
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    fn tick(&self, message: &str) {
+        if let Some(progress) = &self.progress {
+            progress.tick(message);
+        }
+    }
+
+    /// Clear the spinner, if any, so it doesn't get in the way of what we print next.
+    pub fn finish_progress(&self) {
+        if let Some(progress) = &self.progress {
+            progress.finish();
+        }
+    }
+
+    /// Every known package with its latest version.
+    pub fn latest_versions(&self) -> BTreeMap<package::Name, Version> {
+        self.versions
+            .iter()
+            .filter_map(|(id, versions)| match id {
+                PackageId::Pkg(name) => versions.iter().max().map(|v| (name.clone(), *v)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether the package shows up in the list of available packages at all.
+    pub fn is_known(&self, name: &package::Name) -> bool {
+        self.versions.contains_key(&PackageId::Pkg(name.clone()))
+    }
+
+    /// Whether this exact version of the package shows up in the list of available packages.
+    pub fn is_available(&self, name: &package::Name, version: &Version) -> bool {
+        self.versions
+            .get(&PackageId::Pkg(name.clone()))
+            .is_some_and(|versions| versions.contains(version))
+    }
+
+    /// The total number of known package versions, which changes whenever the registry does.
+    pub fn known_versions(&self) -> usize {
+        self.versions.values().map(Vec::len).sum()
+    }
+
+    /// The versions picked by an earlier solve that was memoized under `key`.
+    pub fn memoized_solution(&self, key: u64) -> Option<HashMap<PackageId, Version>> {
+        let file = File::open(self.solution_path(key)).ok()?;
+        let versions: HashMap<String, Version> =
+            bincode::deserialize_from(BufReader::new(file)).ok()?;
+
+        let mut solution = versions
+            .into_iter()
+            .map(|(name, version)| match name.as_str() {
+                "elm" => Some((PackageId::Elm, version)),
+                _ => name
+                    .parse()
+                    .ok()
+                    .map(|name| (PackageId::Pkg(name), version)),
+            })
+            .collect::<Option<HashMap<_, _>>>()?;
+        solution.insert(PackageId::Root, Self::root().version);
+        Some(solution)
+    }
+
+    /// Remember the versions picked in a solve, so an identical solve can skip straight to them.
+    pub fn memoize_solution(
+        &self,
+        key: u64,
+        g: &solver::Graph<solver::Summary<PackageId>>,
+    ) -> Result<()> {
+        let versions: HashMap<String, Version> = g
+            .raw_nodes()
+            .iter()
+            .filter_map(|node| match &node.weight.id {
+                PackageId::Root => None,
+                PackageId::Elm => Some(("elm".to_string(), node.weight.version)),
+                PackageId::Pkg(name) => Some((name.to_string(), node.weight.version)),
+            })
+            .collect();
+
+        let path = self.solution_path(key);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = File::create(path)?;
+        bincode::serialize_into(BufWriter::new(file), &versions)?;
+        Ok(())
+    }
+
+    fn solution_path(&self, key: u64) -> PathBuf {
+        let mut p_path = self.elm_home.clone();
+        p_path.push(format!("elm-json/solutions/{:016x}.dat", key));
+        p_path
+    }
+
+    /// The license of a package, known once its dependencies have been retrieved.
+    pub fn license(&self, pkg: &Summary) -> Option<&str> {
+        self.licenses.get(pkg).map(String::as_str)
+    }
+
     pub fn add_preferred_versions<T>(&mut self, versions: T)
     where
         T: IntoIterator<Item = (PackageId, Version)>,
@@ -244,22 +642,66 @@ impl Retriever {
         self.preferred_versions.extend(versions);
     }
 
+    /// Unlike preferred versions, which are the only ones considered, these are merely tried
+    /// first: when the constraints rule them out, another version is picked as usual.
+    pub fn add_hinted_versions<T>(&mut self, versions: T)
+    where
+        T: IntoIterator<Item = (PackageId, Version)>,
+    {
+        self.hinted_versions.extend(versions);
+    }
+
     fn fetch_deps(&mut self, pkg: &Summary) -> Result<Vec<Incompatibility<PackageId>>> {
         debug!("Fetching dependencies for {}@{}", pkg.id, pkg.version);
 
-        if self.offline {
+        if self.options.offline {
             warn!("Attempting to fetch deps for {:#?}", pkg);
+            self.would_fetch(&format!("the elm.json for {}@{}", pkg.id, pkg.version))?;
             bail!("I need to fetch dependencies from package.elm-lang.org but I'm working in offline mode!");
         }
 
+        self.tick(&format!("Fetching {}@{}", pkg.id, pkg.version));
         let url = format!(
             "https://package.elm-lang.org/packages/{}/{}/elm.json",
             pkg.id, pkg.version
         );
-        let response = isahc::get(url)?;
+        let response = isahc::get(url).with_context(|| {
+            format!(
+                "I couldn't download the elm.json for {}@{} from package.elm-lang.org. Check your internet connection, or use --offline to only use packages that are already cached.",
+                pkg.id, pkg.version
+            )
+        })?;
+
+        // Versions without an elm.json, e.g. because they were yanked, can't be used, but the
+        // solver can still look for another version rather than giving up entirely.
+        if response.status() == isahc::http::StatusCode::NOT_FOUND {
+            warn!(
+                "package.elm-lang.org has no elm.json for {}@{}, skipping that version",
+                pkg.id, pkg.version
+            );
+            let unavailable = vec![Incompatibility::new(
+                indexmap!(pkg.id.clone() => pkg.version.into()),
+                IncompatibilityCause::Unavailable,
+            )];
+            self.deps_cache.insert(pkg.clone(), unavailable.clone());
+            return Ok(unavailable);
+        }
+
         let info: package::Package = serde_json::from_reader(response.into_body())?;
 
-        let path = Self::cached_json_path(pkg)?;
+        if let Err(e) = self.cache_package(pkg, &info) {
+            warn!(
+                "Failed to cache the elm.json for {}@{}: {}",
+                pkg.id, pkg.version, e
+            );
+        }
+
+        self.stats.deps_fetched += 1;
+        Ok(self.deps_from_package(pkg, &info))
+    }
+
+    fn cache_package(&self, pkg: &Summary, info: &package::Package) -> Result<()> {
+        let path = self.cached_json_path(pkg);
 
         DirBuilder::new()
             .recursive(true)
@@ -271,6 +713,7 @@ impl Retriever {
             .write(true)
             .read(true)
             .create(true)
+            .truncate(true)
             .open(path.clone())
             .map_err(|_| {
                 anyhow!(
@@ -280,8 +723,18 @@ impl Retriever {
             })?;
         let mut serializer = serde_json::Serializer::new(file);
         info.serialize(&mut serializer)?;
+        Ok(())
+    }
 
-        Ok(self.deps_from_package(pkg, &info))
+    /// Called whenever we'd need the network for `what`, which isn't allowed with `--locked`.
+    fn would_fetch(&self, what: &str) -> Result<()> {
+        if self.options.locked {
+            bail!(
+                "I need to download {} from package.elm-lang.org, but that isn't cached and --locked doesn't allow me to fetch it. Run without --locked once to fill the cache.",
+                what
+            );
+        }
+        Ok(())
     }
 
     fn read_stored_deps(
@@ -295,26 +748,74 @@ impl Retriever {
             pkg.id, pkg.version
         );
 
-        let mut p_path = Self::packages_path()?;
+        let p_path = self.stored_json_path(elm_version, extra, pkg);
+        let file = File::open(p_path)?;
+        let reader = BufReader::new(file);
+        let info: package::Package = serde_json::from_reader(reader)?;
+        check_version(pkg, &info)?;
+
+        self.stats.deps_read += 1;
+        Ok(self.deps_from_package(pkg, &info))
+    }
+
+    fn stored_json_path(&self, elm_version: &str, extra: &str, pkg: &Summary) -> PathBuf {
+        let mut p_path = self.elm_home.clone();
         p_path.push(format!(
             "{}/package{}/{}/{}/elm.json",
             elm_version, extra, pkg.id, pkg.version
         ));
+        p_path
+    }
 
-        let file = File::open(p_path)?;
-        let reader = BufReader::new(file);
-        let info: package::Package = serde_json::from_reader(reader)?;
+    /// Whether the elm.json of a package can be read without going over the network.
+    fn is_cached(&self, pkg: &Summary) -> bool {
+        self.deps_cache.contains_key(pkg)
+            || [
+                self.stored_json_path("0.19.0", "", pkg),
+                self.stored_json_path("0.19.1", "s", pkg),
+                self.cached_json_path(pkg),
+            ]
+            .iter()
+            .any(|path| path.exists())
+    }
 
-        Ok(self.deps_from_package(pkg, &info))
+    /// Keep track of the packages the solver looked at, warning once when there are suspiciously
+    /// many of them.
+    fn consider(&mut self, pkg: &PackageId) {
+        if self.considered.insert(pkg.clone())
+            && self.considered.len() == CONSIDERED_PACKAGES_WARNING + 1
+        {
+            warn!(
+                "Solving involves more than {} packages, which is a lot more than usual. Check your elm.json and registry for constraints that pull in too much.",
+                CONSIDERED_PACKAGES_WARNING
+            );
+        }
+    }
+
+    fn pick<'a, I>(&self, pkg: &PackageId, versions: I) -> Option<Version>
+    where
+        I: Iterator<Item = &'a Version>,
+    {
+        let mode = if self.minimized.contains(pkg) {
+            &Mode::Minimize
+        } else {
+            &self.mode
+        };
+        versions
+            .max_by(|x, y| match mode {
+                Mode::Minimize => y.cmp(x),
+                Mode::Maximize => x.cmp(y),
+            })
+            .cloned()
     }
 
-    fn cached_json_path(pkg: &Summary) -> Result<PathBuf> {
-        let mut p_path = Self::packages_path()?;
+    fn cached_json_path(&self, pkg: &Summary) -> PathBuf {
+        let mut p_path = self.elm_home.clone();
         p_path.push(format!(
             "elm-json/packages/{}/{}/elm.json",
             pkg.id, pkg.version
         ));
-        Ok(p_path)
+        p_path
     }
 
     fn read_cached_deps(&mut self, pkg: &Summary) -> Result<Vec<Incompatibility<PackageId>>> {
@@ -323,11 +824,17 @@ impl Retriever {
             pkg.id, pkg.version
         );
 
-        let path = Self::cached_json_path(pkg)?;
-        let file = File::open(path)?;
+        let path = self.cached_json_path(pkg);
+        let file = File::open(&path)?;
         let reader = BufReader::new(file);
         let info: package::Package = serde_json::from_reader(reader)?;
+        if let Err(e) = check_version(pkg, &info) {
+            // This is our own cache, so drop the bad entry and let it be fetched again.
+            let _ = fs::remove_file(&path);
+            return Err(e);
+        }
 
+        self.stats.deps_read += 1;
         Ok(self.deps_from_package(pkg, &info))
     }
 
@@ -355,12 +862,17 @@ impl Retriever {
 
         debug!("Caching incompatibilities {:#?}", deps);
 
+        self.licenses
+            .insert(pkg.clone(), info.license().to_string());
+
         self.deps_cache.insert(pkg.clone(), deps.clone());
         deps
     }
 
-    fn packages_path() -> Result<PathBuf> {
-        env::var("ELM_HOME")
+    /// Resolve `ELM_HOME` (or the default location) to an absolute path, creating it if needed,
+    /// so a relative `ELM_HOME` doesn't depend on the directory we're invoked from.
+    fn elm_home() -> Result<PathBuf> {
+        let path = env::var("ELM_HOME")
             .map(PathBuf::from)
             .or_else(|_| {
                 if cfg!(windows) {
@@ -381,7 +893,17 @@ impl Retriever {
                         .ok_or_else(|| anyhow!("No home directory found?"))
                 }
             })
-            .map_err(|e| anyhow!("{}", e))
+            .map_err(|e| anyhow!("{}", e))?;
+
+        fs::create_dir_all(&path)
+            .and_then(|_| path.canonicalize())
+            .map_err(|e| {
+                anyhow!(
+                    "I couldn't use {} as ELM_HOME: {}",
+                    path.to_string_lossy(),
+                    e
+                )
+            })
     }
 
     fn root() -> Summary {
@@ -389,6 +911,35 @@ impl Retriever {
     }
 }
 
+/// Make sure an elm.json read from disk really belongs to the version it was stored under, so a
+/// corrupted or misplaced file can't feed the wrong dependencies into a solve.
+fn check_version(pkg: &Summary, info: &package::Package) -> Result<()> {
+    if info.version() != pkg.version {
+        warn!(
+            "The elm.json stored for {}@{} says it's version {}, ignoring it",
+            pkg.id,
+            pkg.version,
+            info.version()
+        );
+        bail!(
+            "The elm.json stored for {}@{} is for version {} instead",
+            pkg.id,
+            pkg.version,
+            info.version()
+        );
+    }
+    Ok(())
+}
+
+/// Sort the versions of every package and drop duplicates, which appending newly fetched versions
+/// to the cached ones could otherwise introduce.
+fn dedup_versions(versions: &mut HashMap<package::Name, Vec<Version>>) {
+    for vs in versions.values_mut() {
+        vs.sort();
+        vs.dedup();
+    }
+}
+
 impl retriever::Retriever for Retriever {
     type PackageId = self::PackageId;
 
@@ -400,6 +951,7 @@ impl retriever::Retriever for Retriever {
         if pkg.id == PackageId::Elm {
             return Ok(Vec::new());
         }
+        self.consider(&pkg.id);
         self.deps_cache
             .get(pkg)
             .cloned()
@@ -434,18 +986,96 @@ impl retriever::Retriever for Retriever {
                     con
                 )
             }
+        } else if let Some(version) = self
+            .hinted_versions
+            .get(pkg)
+            .filter(|v| con.satisfies(v) && self.versions.get(pkg).is_some_and(|vs| vs.contains(v)))
+        {
+            Ok(*version)
         } else if let Some(versions) = self.versions.get(pkg) {
-            versions
-                .iter()
-                .filter(|v| con.satisfies(v))
-                .max_by(|x, y| match self.mode {
-                    Mode::Minimize => y.cmp(x),
-                    Mode::Maximize => x.cmp(y),
-                })
-                .cloned()
+            let satisfying: Vec<&Version> = versions.iter().filter(|v| con.satisfies(v)).collect();
+
+            // Among the satisfying versions, try the ones we won't have to download first.
+            let cached = if self.options.prefer_cached {
+                self.pick(
+                    pkg,
+                    satisfying
+                        .iter()
+                        .copied()
+                        .filter(|v| self.is_cached(&Summary::new(pkg.clone(), **v))),
+                )
+            } else {
+                None
+            };
+
+            cached
+                .or_else(|| self.pick(pkg, satisfying.into_iter()))
                 .ok_or_else(|| anyhow!("Failed to find a version for {}", pkg))
         } else {
             bail!("Unknown package {}", pkg)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_versions() {
+        let name: package::Name = "elm/core".parse().unwrap();
+        let mut versions = HashMap::new();
+        versions.insert(
+            name.clone(),
+            vec![
+                Version::new(1, 0, 2),
+                Version::new(1, 0, 0),
+                Version::new(1, 0, 2),
+            ],
+        );
+
+        dedup_versions(&mut versions);
+
+        assert_eq!(
+            versions[&name],
+            vec![Version::new(1, 0, 0), Version::new(1, 0, 2)]
+        );
+    }
+
+    fn offline_retriever(locked: bool) -> Retriever {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let options = Options {
+            offline: true,
+            locked,
+            registry_file: Some(fixtures.join("registry.json")),
+            ..Options::default()
+        };
+        Retriever::with_elm_home(&Constraint::any(), &options, fixtures.join("elm_home")).unwrap()
+    }
+
+    #[test]
+    fn test_set_offline_mid_session() {
+        let json: package::Name = "elm/json".parse().unwrap();
+        let deps = [(json, Range::exactly(Version::new(1, 1, 3)))];
+        let mut retriever = offline_retriever(false);
+
+        retriever.set_offline(false).unwrap();
+        assert!(!retriever.is_offline());
+        retriever.set_offline(true).unwrap();
+        assert!(retriever.is_offline());
+
+        retriever.add_deps(&deps);
+        let solution = solver::Resolver::new(&mut retriever).solve().unwrap();
+        assert!(solution
+            .raw_nodes()
+            .iter()
+            .any(|node| node.weight.id == PackageId::Pkg("elm/core".parse().unwrap())));
+    }
+
+    #[test]
+    fn test_locked_stays_offline() {
+        let mut retriever = offline_retriever(true);
+        retriever.set_offline(false).unwrap();
+        assert!(retriever.is_offline());
+    }
+}
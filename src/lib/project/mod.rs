@@ -1,12 +1,20 @@
 use crate::{
+    interrupt,
     package::{self, retriever},
     semver::{Range, Strictness, Version},
     solver,
 };
-use petgraph::{self, visit::IntoNodeReferences};
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::{BTreeMap, HashSet};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+pub mod validate;
 
 pub use crate::package::Package;
 
@@ -17,6 +25,189 @@ pub enum Project {
     Package(Package),
 }
 
+impl Project {
+    /// Read and parse an elm.json file. Failing to read the file results in an `io::Error`.
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Like `load`, but ignoring `//` and `/* */` comments. These can't be kept when the project
+    /// is saved again.
+    pub fn load_with_comments(path: &Path) -> Result<Self> {
+        Self::parse(&strip_comments(&fs::read_to_string(path)?))
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        serde_json::from_str(contents).map_err(|e| {
+            if is_legacy_elm_json(contents) {
+                legacy_elm_json_error()
+            } else {
+                e.into()
+            }
+        })
+    }
+
+    /// Read only the `type` of an elm.json file, either `"application"` or `"package"`, so we can
+    /// tell what we're dealing with even when the rest of the file doesn't parse.
+    pub fn detect_type(path: &Path) -> Result<&'static str> {
+        // Comments can't hurt here, so there's no need to complain about them twice.
+        let contents = strip_comments(&fs::read_to_string(path)?);
+        let value: Value = serde_json::from_str(&contents)?;
+        match value.get("type").and_then(Value::as_str) {
+            Some("application") => Ok("application"),
+            Some("package") => Ok("package"),
+            Some(other) => Err(anyhow!(
+                "The type of an elm.json should be either \"application\" or \"package\", not \"{}\".",
+                other
+            )),
+            None if is_legacy_elm_json(&contents) => Err(legacy_elm_json_error()),
+            None => Err(anyhow!("This elm.json doesn't say whether it's an application or a package.")),
+        }
+    }
+
+    /// Write the project to an elm.json file, formatted the way the Elm compiler does.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        self.save_with_indent(path, "    ")
+    }
+
+    pub fn save_with_indent(&self, path: &Path, indent: &str) -> Result<()> {
+        self.save_with(path, indent, package::DependencyOrder::Canonical)
+    }
+
+    pub fn save_with(
+        &self,
+        path: &Path,
+        indent: &str,
+        order: package::DependencyOrder,
+    ) -> Result<()> {
+        // Write next to the original and rename over it once everything is written, so being
+        // interrupted halfway never leaves a truncated elm.json behind. Going through symlinks
+        // keeps them intact rather than replacing them with a regular file.
+        // Once Ctrl-C was pressed, the original is left alone, even if the new contents were
+        // already written.
+        interrupt::check()?;
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let tmp_path = temp_path(&path);
+        let result = self.write_to(&tmp_path, indent, order).and_then(|()| {
+            interrupt::check()?;
+            if let Ok(metadata) = fs::metadata(&path) {
+                fs::set_permissions(&tmp_path, metadata.permissions())?;
+            }
+            fs::rename(&tmp_path, &path)?;
+            Ok(())
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        result
+    }
+
+    /// Borrow the project for writing, with its dependencies in the given order.
+    fn ordered(&self, order: package::DependencyOrder) -> OrderedProject<'_> {
+        match self {
+            Project::Application(app) => OrderedProject::Application(app.ordered(order)),
+            Project::Package(pkg) => OrderedProject::Package(pkg.ordered(order)),
+        }
+    }
+
+    fn write_to(&self, path: &Path, indent: &str, order: package::DependencyOrder) -> Result<()> {
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(writer, formatter);
+        self.ordered(order).serialize(&mut serializer)?;
+        let mut writer = serializer.into_inner();
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum OrderedProject<'a> {
+    Application(OrderedApplication<'a>),
+    Package(package::OrderedPackage<'a>),
+}
+
+/// A hidden file in the same directory as `path`, so renaming it over `path` doesn't cross
+/// filesystems.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map_or_else(|| "elm.json".into(), |name| name.to_string_lossy());
+    path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+fn legacy_elm_json_error() -> anyhow::Error {
+    anyhow!(
+        "This looks like an elm-package.json from Elm 0.18 or earlier, which isn't supported. \
+         You can use elm-upgrade (https://github.com/avh4/elm-upgrade) to migrate your project to Elm 0.19 first."
+    )
+}
+
+/// Blank out `//` and `/* */` comments outside of strings. Newlines are kept, so errors still point
+/// at the right line.
+pub fn strip_comments(contents: &str) -> String {
+    let mut stripped = String::with_capacity(contents.len());
+    let mut chars = contents.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_string = !in_string,
+            '\\' if in_string => {
+                stripped.push(c);
+                if let Some(escaped) = chars.next() {
+                    stripped.push(escaped);
+                }
+                continue;
+            }
+            '/' if !in_string && chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|c| *c != '\n') {
+                    chars.next();
+                }
+                continue;
+            }
+            '/' if !in_string && chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        stripped.push(c);
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        stripped.push(c);
+    }
+
+    stripped
+}
+
+fn is_legacy_elm_json(contents: &str) -> bool {
+    const LEGACY_KEYS: &[&str] = &["repository", "exact-dependencies", "native-modules"];
+
+    match serde_json::from_str::<Value>(contents) {
+        Ok(Value::Object(fields)) => {
+            !fields.contains_key("type")
+                && (fields.contains_key("dependencies")
+                    || LEGACY_KEYS.iter().any(|k| fields.contains_key(*k)))
+        }
+        _ => false,
+    }
+}
+
+/// Key under which elm-json keeps its own settings in an application's elm.json.
+const ELM_JSON_KEY: &str = "elm-json";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "kebab-case")]
 pub struct Application {
@@ -30,10 +221,61 @@ pub struct Application {
 
 #[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
 pub struct AppDependencies {
+    #[serde(deserialize_with = "package::deserialize_dependencies")]
     pub direct: BTreeMap<package::Name, Version>,
+    #[serde(deserialize_with = "package::deserialize_dependencies")]
     pub indirect: BTreeMap<package::Name, Version>,
 }
 
+/// An application borrowed for writing, with its direct dependencies in a particular order.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct OrderedApplication<'a> {
+    source_directories: &'a [String],
+    elm_version: &'a Version,
+    dependencies: OrderedAppDependencies<'a>,
+    test_dependencies: OrderedAppDependencies<'a>,
+    #[serde(flatten)]
+    other: &'a BTreeMap<String, Value>,
+}
+
+/// Indirect dependencies are only ever touched by tools, so they're always in canonical order.
+#[derive(Serialize)]
+struct OrderedAppDependencies<'a> {
+    direct: package::OrderedDependencies<'a, Version>,
+    indirect: &'a BTreeMap<package::Name, Version>,
+}
+
+impl AppDependencies {
+    fn ordered(&self, order: package::DependencyOrder) -> OrderedAppDependencies<'_> {
+        OrderedAppDependencies {
+            direct: package::OrderedDependencies::new(&self.direct, order),
+            indirect: &self.indirect,
+        }
+    }
+}
+
+/// Every package in a solution with its chosen version, without splitting them into direct and
+/// indirect dependencies.
+pub fn flatten(
+    g: &solver::Graph<solver::Summary<retriever::PackageId>>,
+) -> BTreeMap<package::Name, Version> {
+    g.raw_nodes()
+        .iter()
+        .filter_map(|node| match &node.weight.id {
+            retriever::PackageId::Pkg(name) => Some((name.clone(), node.weight.version)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Split a solution into the `dependencies` and `test-dependencies` of an application.
+///
+/// Only packages reachable from the production `direct_names` end up in `dependencies.indirect`;
+/// anything needed solely by test-dependencies goes to `test-dependencies.indirect`. A direct
+/// test-dependency is never also listed as a test-indirect one, even when another
+/// test-dependency depends on it. It may be a production indirect dependency too, which is the
+/// one overlap elm allows.
 pub fn reconstruct(
     direct_names: &[package::Name],
     g: &solver::Graph<solver::Summary<retriever::PackageId>>,
@@ -42,57 +284,52 @@ pub fn reconstruct(
     let mut indirect = BTreeMap::new();
     let mut test_direct = BTreeMap::new();
     let mut test_indirect = BTreeMap::new();
-    let mut visited: HashSet<usize> = HashSet::new();
-    let mut test_idxs: Vec<usize> = Vec::new();
 
-    let root = g.node_references().next().unwrap().0;
+    let root = match solver::root(g) {
+        Some(root) => root,
+        None => return (AppDependencies::default(), AppDependencies::default()),
+    };
 
+    let mut prod_idxs = Vec::new();
+    let mut test_idxs = Vec::new();
     for idx in g.neighbors(root) {
-        let item = &g[idx];
-
-        if let retriever::PackageId::Pkg(name) = &item.id {
+        if let retriever::PackageId::Pkg(name) = &g[idx].id {
             if direct_names.contains(name) {
-                visited.insert(idx.index());
-
-                direct.insert(name.clone(), item.version);
-                let mut dfs = petgraph::visit::Dfs::new(&g, idx);
-                while let Some(nx) = dfs.next(&g) {
-                    if visited.contains(&nx.index()) {
-                        continue;
-                    }
-                    visited.insert(nx.index());
-                    let item = &g[nx];
-
-                    if let retriever::PackageId::Pkg(dep) = &item.id {
-                        if direct_names.contains(dep) {
-                            continue;
-                        }
-                        indirect.insert(dep.clone(), item.version);
-                    }
-                }
+                direct.insert(name.clone(), g[idx].version);
+                prod_idxs.push(idx);
             } else {
-                test_idxs.push(idx.index());
+                test_direct.insert(name.clone(), g[idx].version);
+                test_idxs.push(idx);
             }
         }
     }
 
-    for idx in test_idxs {
-        let idx = petgraph::graph::NodeIndex::new(idx);
-        let item = &g[idx];
-        if let retriever::PackageId::Pkg(name) = &item.id {
-            visited.insert(idx.index());
-            test_direct.insert(name.clone(), item.version);
-
-            let mut bfs = petgraph::visit::Bfs::new(&g, idx);
-            while let Some(nx) = bfs.next(&g) {
-                if visited.contains(&nx.index()) {
-                    continue;
+    // Everything production code needs has to be known before looking at the test-dependencies,
+    // so a package shared by both is never mistaken for a test-only one.
+    let mut visited: HashSet<petgraph::graph::NodeIndex> = HashSet::new();
+    for idx in prod_idxs {
+        let mut dfs = petgraph::visit::Dfs::new(&g, idx);
+        while let Some(nx) = dfs.next(&g) {
+            if !visited.insert(nx) {
+                continue;
+            }
+            if let retriever::PackageId::Pkg(dep) = &g[nx].id {
+                if !direct.contains_key(dep) {
+                    indirect.insert(dep.clone(), g[nx].version);
                 }
-                visited.insert(nx.index());
-                let item = &g[nx];
+            }
+        }
+    }
 
-                if let retriever::PackageId::Pkg(dep) = &item.id {
-                    test_indirect.insert(dep.clone(), item.version);
+    for idx in test_idxs {
+        let mut bfs = petgraph::visit::Bfs::new(&g, idx);
+        while let Some(nx) = bfs.next(&g) {
+            if !visited.insert(nx) {
+                continue;
+            }
+            if let retriever::PackageId::Pkg(dep) = &g[nx].id {
+                if !test_direct.contains_key(dep) {
+                    test_indirect.insert(dep.clone(), g[nx].version);
                 }
             }
         }
@@ -120,7 +357,10 @@ impl From<solver::Graph<solver::Summary<retriever::PackageId>>> for AppDependenc
     fn from(g: solver::Graph<solver::Summary<retriever::PackageId>>) -> Self {
         let mut direct: BTreeMap<package::Name, Version> = BTreeMap::new();
         let mut indirect: BTreeMap<package::Name, Version> = BTreeMap::new();
-        let root = g.node_references().next().unwrap().0;
+        let root = match solver::root(&g) {
+            Some(root) => root,
+            None => return Self::default(),
+        };
         let mut bfs = petgraph::visit::Bfs::new(&g, root);
 
         while let Some(nx) = bfs.next(&g) {
@@ -186,6 +426,16 @@ impl Application {
             .collect()
     }
 
+    fn ordered(&self, order: package::DependencyOrder) -> OrderedApplication<'_> {
+        OrderedApplication {
+            source_directories: &self.source_directories,
+            elm_version: &self.elm_version,
+            dependencies: self.dependencies.ordered(order),
+            test_dependencies: self.test_dependencies.ordered(order),
+            other: &self.other,
+        }
+    }
+
     pub fn elm_version(&self) -> Version {
         self.elm_version
     }
@@ -197,6 +447,33 @@ impl Application {
             ..self
         }
     }
+
+    pub fn with_source_directories(self, source_directories: Vec<String>) -> Self {
+        Self {
+            source_directories,
+            ..self
+        }
+    }
+
+    /// Whether the indirect dependencies were marked as authoritative by `install --freeze`.
+    pub fn is_frozen(&self) -> bool {
+        self.other
+            .get(ELM_JSON_KEY)
+            .and_then(|settings| settings.get("frozen"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    }
+
+    pub fn frozen(mut self) -> Self {
+        let settings = self
+            .other
+            .entry(ELM_JSON_KEY.to_string())
+            .or_insert_with(|| Value::Object(serde_json::Map::new()));
+        if let Value::Object(settings) = settings {
+            settings.insert("frozen".to_string(), Value::Bool(true));
+        }
+        self
+    }
 }
 
 impl Default for Application {
@@ -204,3 +481,142 @@ impl Default for Application {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_comments() {
+        let commented = r#"{
+    // The type
+    "type": "application", /* inline */
+    "url": "https://example.com/*not-a-comment*/",
+    "quote": "a \" // still a string",
+    /* spanning
+       lines */
+    "done": true
+}"#;
+        let value: Value = serde_json::from_str(&strip_comments(commented)).unwrap();
+        assert_eq!(value["type"], "application");
+        assert_eq!(value["url"], "https://example.com/*not-a-comment*/");
+        assert_eq!(value["quote"], "a \" // still a string");
+        assert_eq!(value["done"], true);
+        assert_eq!(
+            strip_comments(commented).lines().count(),
+            commented.lines().count()
+        );
+    }
+
+    #[test]
+    fn test_with_source_directories() {
+        let app = Application::new()
+            .with_source_directories(vec!["src".to_string(), "../shared".to_string()]);
+        let json = serde_json::to_value(Project::Application(app)).unwrap();
+        assert_eq!(
+            json["source-directories"],
+            serde_json::json!(["src", "../shared"])
+        );
+        assert_eq!(json["elm-version"], "0.19.1");
+    }
+
+    #[test]
+    fn test_ordered_application() {
+        let mut app = Application::new();
+        let core = app.dependencies.direct.pop_first().unwrap();
+        let names = ["Zwilias/elm-json", "elm/core"];
+        for name in names {
+            let name: package::Name = name.parse().unwrap();
+            app.dependencies.direct.insert(name.clone(), core.1);
+            app.dependencies.indirect.insert(name, core.1);
+        }
+
+        let json =
+            serde_json::to_string(&app.ordered(package::DependencyOrder::IgnoreCase)).unwrap();
+        let direct = &json[json.find("\"direct\"").unwrap()..json.find("\"indirect\"").unwrap()];
+        let indirect = &json[json.find("\"indirect\"").unwrap()..];
+        assert!(direct.find("elm/core") < direct.find("Zwilias/elm-json"));
+        assert!(indirect.find("Zwilias/elm-json") < indirect.find("elm/core"));
+    }
+
+    #[test]
+    fn test_reconstruct_lists_test_dependencies_once() {
+        let name = |s: &str| s.parse::<package::Name>().unwrap();
+        let mut g = solver::Graph::new();
+        let mut node = |id, version| g.add_node(solver::Summary::new(id, version));
+        let root = node(retriever::PackageId::Root, Version::new(1, 0, 0));
+        let html = node(
+            retriever::PackageId::Pkg(name("elm/html")),
+            Version::new(1, 0, 0),
+        );
+        let core = node(
+            retriever::PackageId::Pkg(name("elm/core")),
+            Version::new(1, 0, 5),
+        );
+        let test = node(
+            retriever::PackageId::Pkg(name("elm-explorations/test")),
+            Version::new(1, 2, 2),
+        );
+        let random = node(
+            retriever::PackageId::Pkg(name("elm/random")),
+            Version::new(1, 0, 0),
+        );
+        let json = node(
+            retriever::PackageId::Pkg(name("elm/json")),
+            Version::new(1, 1, 3),
+        );
+        for (from, to) in [
+            (root, html),
+            (root, random),
+            (root, test),
+            (html, core),
+            (html, json),
+            (test, random),
+            (test, core),
+            (random, core),
+        ] {
+            g.add_edge(from, to, ());
+        }
+
+        let (deps, test_deps) = reconstruct(&[name("elm/html")], &g);
+        let names = |deps: &BTreeMap<package::Name, Version>| {
+            deps.keys().map(ToString::to_string).collect::<Vec<_>>()
+        };
+        assert_eq!(names(&deps.direct), ["elm/html"]);
+        assert_eq!(names(&deps.indirect), ["elm/core", "elm/json"]);
+        assert_eq!(
+            names(&test_deps.direct),
+            ["elm/random", "elm-explorations/test"]
+        );
+        assert!(test_deps.indirect.is_empty());
+    }
+
+    #[test]
+    fn test_flatten() {
+        let name = |s: &str| s.parse::<package::Name>().unwrap();
+        let mut g = solver::Graph::new();
+        let mut node = |id, version| g.add_node(solver::Summary::new(id, version));
+        let root = node(retriever::PackageId::Root, Version::new(1, 0, 0));
+        let elm = node(retriever::PackageId::Elm, Version::new(0, 19, 1));
+        let json = node(
+            retriever::PackageId::Pkg(name("elm/json")),
+            Version::new(1, 1, 3),
+        );
+        let core = node(
+            retriever::PackageId::Pkg(name("elm/core")),
+            Version::new(1, 0, 5),
+        );
+        for (from, to) in [(root, elm), (root, json), (json, core)] {
+            g.add_edge(from, to, ());
+        }
+
+        let flat = flatten(&g);
+        assert_eq!(
+            flat.into_iter().collect::<Vec<_>>(),
+            [
+                (name("elm/core"), Version::new(1, 0, 5)),
+                (name("elm/json"), Version::new(1, 1, 3)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! Checking an elm.json field by field, so every problem can be reported at once rather than only
+//! the first one deserializing runs into.
+
+use crate::{
+    package::{self, retriever},
+    semver::Version,
+};
+use serde_json::{Map, Value};
+use std::{fmt, str::FromStr};
+
+/// A single problem, along with where in the elm.json it was found.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Issue {
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.location.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.location, self.message)
+        }
+    }
+}
+
+/// Every problem with the contents of an elm.json, in the order the fields are checked. This is
+/// a superset of what loading the elm.json rejects: anything loading fails on that none of the
+/// checks here catch, like a package listed twice, is reported as well.
+pub fn issues(contents: &str) -> Vec<Issue> {
+    let mut checker = Checker::default();
+    match serde_json::from_str::<Value>(contents) {
+        Ok(Value::Object(fields)) => checker.project(&fields),
+        Ok(_) => checker.issue("", "An elm.json should contain a JSON object."),
+        Err(e) => checker.issue("", format!("This isn't valid JSON: {}", e)),
+    }
+    if checker.issues.is_empty() {
+        if let Err(e) = super::Project::parse(contents) {
+            checker.issue("", e);
+        }
+    }
+    checker.issues
+}
+
+#[derive(Default)]
+struct Checker {
+    issues: Vec<Issue>,
+}
+
+impl Checker {
+    fn issue(&mut self, location: &str, message: impl ToString) {
+        self.issues.push(Issue {
+            location: location.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    fn project(&mut self, fields: &Map<String, Value>) {
+        match fields.get("type").and_then(Value::as_str) {
+            Some("application") => self.application(fields),
+            Some("package") => self.package(fields),
+            Some(other) => self.issue(
+                "type",
+                format!(
+                    "Expected \"application\" or \"package\", not \"{}\".",
+                    other
+                ),
+            ),
+            None => self.issue("type", "Missing, or not a string."),
+        }
+    }
+
+    fn application(&mut self, fields: &Map<String, Value>) {
+        match fields.get("source-directories") {
+            Some(Value::Array(dirs)) if dirs.iter().all(Value::is_string) => {}
+            Some(_) => self.issue("source-directories", "Expected a list of strings."),
+            None => self.issue("source-directories", "Missing."),
+        }
+        if let Some(version) = self.parsed::<Version>(fields, "elm-version") {
+            if !retriever::known_elm_versions().contains(&version) {
+                self.issue(
+                    "elm-version",
+                    format!("{} isn't an Elm version I know about.", version),
+                );
+            }
+        }
+
+        let mut sections = Vec::new();
+        for key in ["dependencies", "test-dependencies"] {
+            if let Some(deps) = self.object(fields, key, key) {
+                for kind in ["direct", "indirect"] {
+                    let location = format!("{}.{}", key, kind);
+                    if let Some(deps) = self.object(deps, kind, &location) {
+                        self.dependencies::<Version>(deps, &location);
+                        sections.push((location, deps));
+                    }
+                }
+            }
+        }
+        self.overlap(&sections);
+    }
+
+    /// Like elm itself, allow a package in both `dependencies.indirect` and
+    /// `test-dependencies.direct` as long as the versions agree, and nowhere else more than once.
+    fn overlap(&mut self, sections: &[(String, &Map<String, Value>)]) {
+        for (i, (location, deps)) in sections.iter().enumerate() {
+            for (other_location, other) in &sections[i + 1..] {
+                let allowed = location == "dependencies.indirect"
+                    && other_location == "test-dependencies.direct";
+                for (name, version) in deps.iter().filter(|(name, _)| other.contains_key(*name)) {
+                    if !allowed {
+                        self.issue(
+                            other_location,
+                            format!(
+                                "{} is also in {}; a package may only be in one.",
+                                name, location
+                            ),
+                        );
+                    } else if other[name] != *version {
+                        self.issue(
+                            other_location,
+                            format!("{} is at a different version than in {}.", name, location),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn package(&mut self, fields: &Map<String, Value>) {
+        self.parsed::<package::Name>(fields, "name");
+        if let Some(summary) = self.string(fields, "summary") {
+            if let Err(e) = package::validate_summary(summary) {
+                self.issue("summary", e);
+            }
+        }
+        if let Some(license) = self.string(fields, "license") {
+            if !package::is_approved_license(license) {
+                self.issue(
+                    "license",
+                    format!(
+                        "{} isn't an SPDX license package.elm-lang.org accepts.",
+                        license
+                    ),
+                );
+            }
+        }
+        self.parsed::<Version>(fields, "version");
+        self.parsed::<package::Range>(fields, "elm-version");
+
+        match fields.get("exposed-modules") {
+            Some(Value::Array(modules)) if modules.iter().all(Value::is_string) => {}
+            Some(Value::Object(groups))
+                if groups.values().all(|modules| {
+                    modules
+                        .as_array()
+                        .is_some_and(|modules| modules.iter().all(Value::is_string))
+                }) => {}
+            Some(_) => self.issue(
+                "exposed-modules",
+                "Expected a list of module names, or an object of such lists.",
+            ),
+            None => self.issue("exposed-modules", "Missing."),
+        }
+
+        let deps = self.object(fields, "dependencies", "dependencies");
+        let test_deps = self.object(fields, "test-dependencies", "test-dependencies");
+        if let Some(deps) = deps {
+            self.dependencies::<package::Range>(deps, "dependencies");
+        }
+        if let Some(test_deps) = test_deps {
+            self.dependencies::<package::Range>(test_deps, "test-dependencies");
+        }
+        if let (Some(deps), Some(test_deps)) = (deps, test_deps) {
+            for name in test_deps.keys().filter(|name| deps.contains_key(*name)) {
+                self.issue(
+                    "test-dependencies",
+                    format!(
+                        "{} is also in dependencies; a package may only be in one.",
+                        name
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Check that every key is a package name and every value parses as a `V`.
+    fn dependencies<V>(&mut self, deps: &Map<String, Value>, location: &str)
+    where
+        V: FromStr,
+        V::Err: fmt::Display,
+    {
+        let mut names: Vec<package::Name> = Vec::new();
+        for (name, value) in deps {
+            let location = format!("{}.\"{}\"", location, name);
+            match name.parse::<package::Name>() {
+                Ok(name) => {
+                    if let Some(other) = names.iter().find(|other| other.eq_ignore_case(&name)) {
+                        self.issue(
+                            &location,
+                            format!(
+                                "{} and {} only differ in the case of the author, so they're the same package listed twice.",
+                                other, name
+                            ),
+                        );
+                    }
+                    names.push(name);
+                }
+                Err(e) => self.issue(&location, e),
+            }
+            match value.as_str() {
+                Some(value) => {
+                    if let Err(e) = value.parse::<V>() {
+                        self.issue(&location, e);
+                    }
+                }
+                None => self.issue(&location, "Expected a string."),
+            }
+        }
+    }
+
+    fn object<'a>(
+        &mut self,
+        fields: &'a Map<String, Value>,
+        key: &str,
+        location: &str,
+    ) -> Option<&'a Map<String, Value>> {
+        match fields.get(key) {
+            Some(Value::Object(fields)) => Some(fields),
+            Some(_) => {
+                self.issue(location, "Expected an object.");
+                None
+            }
+            None => {
+                self.issue(location, "Missing.");
+                None
+            }
+        }
+    }
+
+    fn string<'a>(&mut self, fields: &'a Map<String, Value>, key: &str) -> Option<&'a str> {
+        match fields.get(key) {
+            Some(Value::String(s)) => Some(s),
+            Some(_) => {
+                self.issue(key, "Expected a string.");
+                None
+            }
+            None => {
+                self.issue(key, "Missing.");
+                None
+            }
+        }
+    }
+
+    fn parsed<T>(&mut self, fields: &Map<String, Value>, key: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        match self.string(fields, key)?.parse::<T>() {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.issue(key, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_application() {
+        let app = r#"{
+            "type": "application",
+            "source-directories": ["src"],
+            "elm-version": "0.19.1",
+            "dependencies": {"direct": {"elm/core": "1.0.5"}, "indirect": {}},
+            "test-dependencies": {"direct": {}, "indirect": {}}
+        }"#;
+        assert_eq!(issues(app), []);
+    }
+
+    #[test]
+    fn test_unknown_elm_version() {
+        let app = r#"{
+            "type": "application",
+            "source-directories": ["src"],
+            "elm-version": "0.19.2",
+            "dependencies": {"direct": {}, "indirect": {}},
+            "test-dependencies": {"direct": {}, "indirect": {}}
+        }"#;
+        let locations: Vec<_> = issues(app).into_iter().map(|i| i.location).collect();
+        assert_eq!(locations, ["elm-version"]);
+    }
+
+    #[test]
+    fn test_application_overlap() {
+        let app = r#"{
+            "type": "application",
+            "source-directories": ["src"],
+            "elm-version": "0.19.1",
+            "dependencies": {
+                "direct": {"elm/core": "1.0.5"},
+                "indirect": {"elm/json": "1.1.3", "elm/html": "1.0.0"}
+            },
+            "test-dependencies": {
+                "direct": {"elm/json": "1.1.3", "elm/html": "1.0.1"},
+                "indirect": {"elm/core": "1.0.5"}
+            }
+        }"#;
+        let locations: Vec<_> = issues(app).into_iter().map(|i| i.location).collect();
+        assert_eq!(
+            locations,
+            ["test-dependencies.indirect", "test-dependencies.direct"]
+        );
+    }
+
+    #[test]
+    fn test_agrees_with_loading() {
+        let duplicate = r#"{
+            "type": "application",
+            "source-directories": ["src"],
+            "elm-version": "0.19.1",
+            "dependencies": {"direct": {"elm/core": "1.0.5", "elm/core": "1.0.4"}, "indirect": {}},
+            "test-dependencies": {"direct": {}, "indirect": {}}
+        }"#;
+        assert!(super::super::Project::parse(duplicate).is_err());
+        assert_eq!(issues(duplicate).len(), 1);
+
+        let case = r#"{
+            "type": "package",
+            "name": "author/project",
+            "summary": "fine",
+            "license": "MIT",
+            "version": "1.0.0",
+            "exposed-modules": [],
+            "elm-version": "0.19.0 <= v < 0.20.0",
+            "dependencies": {"elm/core": "1.0.0 <= v < 2.0.0", "Elm/core": "1.0.0 <= v < 2.0.0"},
+            "test-dependencies": {}
+        }"#;
+        assert!(super::super::Project::parse(case).is_err());
+        assert_eq!(issues(case).len(), 1);
+    }
+
+    #[test]
+    fn test_reports_every_issue() {
+        let pkg = r#"{
+            "type": "package",
+            "name": "Not a name",
+            "summary": "fine",
+            "license": "MIT",
+            "version": "1.x.0",
+            "exposed-modules": [],
+            "elm-version": "0.19.0 <= v < 0.20.0",
+            "dependencies": {"elm/core": "1.0.0 <= v < 2.0.0", "elm/json": "nope"},
+            "test-dependencies": {"elm/core": "1.0.0 <= v < 2.0.0"}
+        }"#;
+        let locations: Vec<_> = issues(pkg).into_iter().map(|i| i.location).collect();
+        assert_eq!(
+            locations,
+            [
+                "name",
+                "version",
+                "dependencies.\"elm/json\"",
+                "test-dependencies"
+            ]
+        );
+    }
+}
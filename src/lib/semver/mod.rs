@@ -50,7 +50,14 @@ use anyhow::{anyhow, Error};
 use indexmap::{indexset, IndexSet};
 use itertools::Itertools;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
-use std::{cmp, fmt, str::FromStr, string::ToString};
+use std::{
+    cmp,
+    collections::HashSet,
+    fmt, ops,
+    str::FromStr,
+    string::ToString,
+    sync::{Mutex, OnceLock, PoisonError},
+};
 
 pub enum Strictness {
     Exact,
@@ -67,11 +74,22 @@ pub enum Relation {
     Equal,
 }
 
-#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+impl Relation {
+    /// Whether two constraints share at least one version.
+    pub fn is_compatible(&self) -> bool {
+        *self != Relation::Disjoint
+    }
+}
+
+/// A `major.minor.patch` version, optionally followed by `+build` metadata. As per the semver
+/// spec, build metadata is ignored when comparing versions; it's only kept around so the version
+/// is written back the way we found it.
+#[derive(Copy, Clone, Debug)]
 pub struct Version {
     major: u64,
     minor: u64,
     patch: u64,
+    build: Option<&'static str>,
 }
 
 impl Version {
@@ -80,37 +98,141 @@ impl Version {
             major,
             minor,
             patch,
+            build: None,
         }
     }
 
+    fn key(&self) -> (u64, u64, u64) {
+        (self.major, self.minor, self.patch)
+    }
+
     pub fn major(&self) -> u64 {
         self.major
     }
+
+    pub fn minor(&self) -> u64 {
+        self.minor
+    }
+
+    pub fn patch(&self) -> u64 {
+        self.patch
+    }
+
+    pub fn build(&self) -> Option<&str> {
+        self.build
+    }
+
+    /// Versions below 1.0.0 make no compatibility promises, so even minor bumps may break.
+    pub fn is_stable(&self) -> bool {
+        self.major >= 1
+    }
+
+    pub fn next_major(&self) -> Self {
+        Self::new(self.major + 1, 0, 0)
+    }
+
+    pub fn next_minor(&self) -> Self {
+        Self::new(self.major, self.minor + 1, 0)
+    }
+
+    pub fn next_patch(&self) -> Self {
+        Self::new(self.major, self.minor, self.patch + 1)
+    }
+}
+
+/// Build metadata is interned, so `Version` can stay `Copy` while every distinct string is only
+/// ever allocated once, however often it's parsed.
+fn intern(build: &str) -> &'static str {
+    static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut interned = INTERNED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    match interned.get(build) {
+        Some(build) => build,
+        None => {
+            let build: &'static str = Box::leak(build.to_string().into_boxed_str());
+            interned.insert(build);
+            build
+        }
+    }
 }
 
 impl FromStr for Version {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<u64> = s
+        let (version, build) = match s.split_once('+') {
+            Some((version, build)) => (version, Some(build)),
+            None => (s, None),
+        };
+
+        let build = match build {
+            Some(build)
+                if build.is_empty()
+                    || build.split('.').any(|identifier| {
+                        identifier.is_empty()
+                            || !identifier
+                                .chars()
+                                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+                    }) =>
+            {
+                return Err(anyhow!("Invalid build metadata in version: {}", s))
+            }
+            Some(build) => Some(intern(build)),
+            None => None,
+        };
+
+        let parts: Vec<u64> = version
             .split('.')
             .map(str::parse)
             .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| anyhow!("{}", e))?;
+            .map_err(|e| anyhow!("Invalid version: {} ({})", s, e))?;
         match parts.as_slice() {
             [major, minor, patch] => Ok(Self {
                 major: *major,
                 minor: *minor,
                 patch: *patch,
+                build,
             }),
             _ => Err(anyhow!("Invalid version: {}", s)),
         }
     }
 }
 
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Version {}
+
+impl std::hash::Hash for Version {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
 impl fmt::Display for Version {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(build) = self.build {
+            write!(f, "+{}", build)?;
+        }
+        Ok(())
     }
 }
 
@@ -313,6 +435,27 @@ impl Range {
         Self { lower, upper }
     }
 
+    /// Creates the range `lower <= v < upper`, or `None` if no version fits in it.
+    pub fn closed_open(lower: Version, upper: Version) -> Option<Self> {
+        Self::new(Interval::Closed(lower), Interval::Open(upper))
+    }
+
+    /// Creates the range of all versions from `lower` onwards.
+    pub fn at_least(lower: Version) -> Self {
+        Self {
+            lower: Interval::Closed(lower),
+            upper: Interval::Unbounded,
+        }
+    }
+
+    /// Creates the range only containing `v`.
+    pub fn exactly(v: Version) -> Self {
+        Self {
+            lower: Interval::Closed(v),
+            upper: Interval::Closed(v),
+        }
+    }
+
     pub fn upper(&self) -> &Interval {
         &self.upper
     }
@@ -361,7 +504,11 @@ impl Range {
         let lower = Interval::Closed(*v);
         let upper = match strictness {
             Strictness::Exact => Interval::Closed(*v),
-            Strictness::Safe => Interval::Open(Version::new(v.major + 1, 0, 0)),
+            Strictness::Safe => Interval::Open(match (v.major, v.minor) {
+                (0, 0) => v.next_patch(),
+                (0, _) => v.next_minor(),
+                _ => v.next_major(),
+            }),
             Strictness::Unsafe => Interval::Unbounded,
         };
 
@@ -371,9 +518,7 @@ impl Range {
 
 impl From<Version> for Range {
     fn from(v: Version) -> Self {
-        let lower = Interval::Closed(v);
-        let upper = Interval::Closed(v);
-        Self { lower, upper }
+        Self::exactly(v)
     }
 }
 
@@ -464,15 +609,16 @@ impl Constraint {
                     let (a2, b2) = (a.clone(), b.clone());
                     let (al, au) = a.take();
                     let (bl, bu) = b.take();
-                    if let (Interval::Open(v), Interval::Closed(w)) = (au, bl) {
-                        if v == w {
-                            let r = Range::new(al, bu).unwrap();
-                            Ok(r)
-                        } else {
-                            Err((a2, b2))
+                    match (au, bl) {
+                        (Interval::Open(v), Interval::Closed(w)) if v == w => {
+                            Ok(Range::new(al, bu).unwrap())
                         }
-                    } else {
-                        Err((a2, b2))
+                        // Versions are discrete, so nothing can fall in between `<= 1.0.5` and
+                        // `>= 1.0.6`.
+                        (Interval::Closed(v), Interval::Closed(w)) if v.next_patch() == w => {
+                            Ok(Range::new(al, bu).unwrap())
+                        }
+                        _ => Err((a2, b2)),
                     }
                 }
             })
@@ -488,6 +634,34 @@ impl Constraint {
         self.set.iter().any(|s| s.satisfies(v))
     }
 
+    /// The lower bound of this `Constraint`, or `None` if it's empty or unbounded below. For an
+    /// open bound, this is the boundary version itself, even though it doesn't satisfy the
+    /// `Constraint`.
+    pub fn min_version(&self) -> Option<Version> {
+        let lowest = self
+            .set
+            .iter()
+            .min_by(|a, b| a.lower().cmp(b.lower(), true))?;
+        match lowest.lower() {
+            Interval::Closed(v) | Interval::Open(v) => Some(*v),
+            Interval::Unbounded => None,
+        }
+    }
+
+    /// The upper bound of this `Constraint`, or `None` if it's empty or unbounded above. For an
+    /// open bound, this is the boundary version itself, even though it doesn't satisfy the
+    /// `Constraint`.
+    pub fn max_version(&self) -> Option<Version> {
+        let highest = self
+            .set
+            .iter()
+            .max_by(|a, b| a.upper().cmp(b.upper(), false))?;
+        match highest.upper() {
+            Interval::Closed(v) | Interval::Open(v) => Some(*v),
+            Interval::Unbounded => None,
+        }
+    }
+
     pub fn intersection(&self, other: &Self) -> Self {
         let mut set = IndexSet::new();
 
@@ -508,7 +682,13 @@ impl Constraint {
         let mut set = self.set.clone();
         set.extend(other.set.clone());
 
-        Self { set }
+        Self::new(set)
+    }
+
+    /// Returns a unified copy of this `Constraint`, merging any ranges that touch or overlap so
+    /// the result can be shown as compactly as possible.
+    pub fn simplify(&self) -> Self {
+        Self::new(self.set.clone())
     }
 
     pub fn difference(&self, other: &Self) -> Self {
@@ -693,6 +873,70 @@ impl Default for Constraint {
     }
 }
 
+impl ops::BitAnd for &Constraint {
+    type Output = Constraint;
+
+    fn bitand(self, other: Self) -> Constraint {
+        self.intersection(other)
+    }
+}
+
+impl ops::BitAnd for Constraint {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        self.intersection(&other)
+    }
+}
+
+impl ops::BitOr for &Constraint {
+    type Output = Constraint;
+
+    fn bitor(self, other: Self) -> Constraint {
+        self.union(other)
+    }
+}
+
+impl ops::BitOr for Constraint {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(&other)
+    }
+}
+
+impl ops::Sub for &Constraint {
+    type Output = Constraint;
+
+    fn sub(self, other: Self) -> Constraint {
+        self.difference(other)
+    }
+}
+
+impl ops::Sub for Constraint {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.difference(&other)
+    }
+}
+
+impl ops::Not for &Constraint {
+    type Output = Constraint;
+
+    fn not(self) -> Constraint {
+        self.complement()
+    }
+}
+
+impl ops::Not for Constraint {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        self.complement()
+    }
+}
+
 impl From<Range> for Constraint {
     fn from(r: Range) -> Self {
         let mut set = IndexSet::new();
@@ -721,7 +965,7 @@ impl fmt::Debug for Constraint {
 
 impl fmt::Display for Constraint {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let items: Vec<Range> = self.set.iter().cloned().collect();
+        let items: Vec<Range> = self.simplify().take().into_iter().collect();
         let items: &[Range] = &items;
         match items {
             [Range {
@@ -733,9 +977,172 @@ impl fmt::Display for Constraint {
             }] => write!(
                 f,
                 "at versions other than {}",
-                Range::new(Interval::Closed(*l), Interval::Open(*u)).unwrap()
+                Range::closed_open(*l, *u).unwrap()
             ),
             _ => write!(f, "{}", items.iter().map(ToString::to_string).join(", ")),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(lower: Interval, upper: Interval) -> Range {
+        Range::new(lower, upper).unwrap()
+    }
+
+    #[test]
+    fn test_relation_is_compatible() {
+        let safe = |v| Constraint::from(Range::from(&v, &Strictness::Safe));
+
+        let minor = safe(Version::new(1, 0, 0)).relation(&safe(Version::new(1, 2, 0)));
+        assert!(minor.is_compatible());
+
+        let major = safe(Version::new(1, 0, 0)).relation(&safe(Version::new(2, 0, 0)));
+        assert_eq!(major, Relation::Disjoint);
+        assert!(!major.is_compatible());
+    }
+
+    #[test]
+    fn test_safe_range_for_unstable_versions() {
+        let safe = |v| Range::from(&v, &Strictness::Safe).to_string();
+
+        assert_eq!(safe(Version::new(1, 2, 3)), "1.2.3 <= v < 2.0.0");
+        assert_eq!(safe(Version::new(0, 2, 3)), "0.2.3 <= v < 0.3.0");
+        assert_eq!(safe(Version::new(0, 0, 3)), "0.0.3 <= v < 0.0.4");
+    }
+
+    #[test]
+    fn test_min_max_version() {
+        let c = Constraint::new(indexset!(
+            range(Closed(Version::new(2, 0, 0)), Open(Version::new(3, 0, 0))),
+            range(Closed(Version::new(1, 0, 0)), Closed(Version::new(1, 0, 5))),
+        ));
+        assert_eq!(c.min_version(), Some(Version::new(1, 0, 0)));
+        assert_eq!(c.max_version(), Some(Version::new(3, 0, 0)));
+
+        assert_eq!(Constraint::any().min_version(), None);
+        assert_eq!(Constraint::any().max_version(), None);
+        assert_eq!(Constraint::empty().min_version(), None);
+    }
+
+    #[test]
+    fn test_next_versions() {
+        let v = Version::new(1, 2, 3);
+        assert_eq!(v.next_major(), Version::new(2, 0, 0));
+        assert_eq!(v.next_minor(), Version::new(1, 3, 0));
+        assert_eq!(v.next_patch(), Version::new(1, 2, 4));
+    }
+
+    #[test]
+    fn test_is_stable() {
+        assert!(!Version::new(0, 19, 1).is_stable());
+        assert!(Version::new(1, 0, 0).is_stable());
+        assert!(Version::new(2, 3, 4).is_stable());
+    }
+
+    #[test]
+    fn test_unify_closed_open_touching() {
+        let c = Constraint::new(indexset!(
+            range(Closed(Version::new(1, 0, 0)), Open(Version::new(2, 0, 0))),
+            range(Closed(Version::new(2, 0, 0)), Open(Version::new(3, 0, 0))),
+        ));
+
+        assert_eq!(c.to_string(), "1.0.0 <= v < 3.0.0");
+    }
+
+    #[test]
+    fn test_unify_closed_closed_adjacent() {
+        let c = Constraint::new(indexset!(
+            range(Closed(Version::new(1, 0, 0)), Closed(Version::new(1, 0, 5))),
+            range(Closed(Version::new(1, 0, 6)), Open(Version::new(2, 0, 0))),
+        ));
+
+        assert_eq!(c.to_string(), "1.0.0 <= v < 2.0.0");
+    }
+
+    #[test]
+    fn test_unify_keeps_gaps() {
+        let c = Constraint::new(indexset!(
+            range(Closed(Version::new(1, 0, 0)), Closed(Version::new(1, 0, 5))),
+            range(Closed(Version::new(1, 1, 0)), Open(Version::new(2, 0, 0))),
+        ));
+
+        assert_eq!(c.retrieve().len(), 2);
+    }
+
+    #[test]
+    fn test_union_is_simplified() {
+        let a: Constraint =
+            range(Closed(Version::new(1, 0, 0)), Open(Version::new(2, 0, 0))).into();
+        let b: Constraint =
+            range(Closed(Version::new(2, 0, 0)), Open(Version::new(3, 0, 0))).into();
+
+        assert_eq!(a.union(&b).retrieve().len(), 1);
+    }
+
+    #[test]
+    fn test_set_operators() {
+        let a: Constraint =
+            range(Closed(Version::new(1, 0, 0)), Open(Version::new(3, 0, 0))).into();
+        let b: Constraint =
+            range(Closed(Version::new(2, 0, 0)), Open(Version::new(4, 0, 0))).into();
+
+        assert_eq!(&a & &b, a.intersection(&b));
+        assert_eq!(&a | &b, a.union(&b));
+        assert_eq!(&a - &b, a.difference(&b));
+        assert_eq!(!&a, a.complement());
+        assert_eq!(a.clone() & b.clone(), a.intersection(&b));
+    }
+
+    #[test]
+    fn test_build_metadata() {
+        let a: Version = "1.0.0+a".parse().unwrap();
+        let b: Version = "1.0.0+b".parse().unwrap();
+
+        assert_eq!(a.cmp(&b), cmp::Ordering::Equal);
+        assert_eq!(a, Version::new(1, 0, 0));
+        assert!(a < "1.0.1+a".parse().unwrap());
+        assert_eq!(a.build(), Some("a"));
+        assert_eq!(
+            "1.0.0+build.5".parse::<Version>().unwrap().to_string(),
+            "1.0.0+build.5"
+        );
+        assert!("1.0.0+".parse::<Version>().is_err());
+        assert!("1.0.0+a..b".parse::<Version>().is_err());
+    }
+
+    #[test]
+    fn test_build_metadata_is_interned() {
+        let a: Version = "1.0.0+interned".parse().unwrap();
+        let b: Version = "2.0.0+interned".parse().unwrap();
+        assert!(std::ptr::eq(a.build().unwrap(), b.build().unwrap()));
+    }
+
+    #[test]
+    fn test_range_helpers() {
+        let (v1, v2) = (Version::new(1, 0, 0), Version::new(2, 0, 0));
+
+        let r = Range::closed_open(v1, v2).unwrap();
+        assert!(r.satisfies(&v1));
+        assert!(!r.satisfies(&v2));
+        assert!(Range::closed_open(v2, v1).is_none());
+        assert!(Range::closed_open(v1, v1).is_none());
+
+        assert!(Range::at_least(v1).satisfies(&Version::new(99, 0, 0)));
+        assert!(!Range::at_least(v2).satisfies(&v1));
+
+        assert_eq!(Range::exactly(v1), range(Closed(v1), Closed(v1)));
+        assert_eq!(Range::exactly(v1).to_string(), "1.0.0");
+    }
+
+    #[test]
+    fn test_version_errors_mention_input() {
+        let err = "1.x.0".parse::<Version>().unwrap_err().to_string();
+        assert!(err.starts_with("Invalid version: 1.x.0"), "{}", err);
+
+        let err = "1.0".parse::<Version>().unwrap_err().to_string();
+        assert_eq!(err, "Invalid version: 1.0");
+    }
+}
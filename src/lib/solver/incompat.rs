@@ -125,11 +125,12 @@ where
                     )
                 } else {
                     format!(
-                        "one of {} must be false",
+                        "these {} requirements cannot all be satisfied together:\n{}",
+                        self.deps.len(),
                         self.deps
                             .iter()
-                            .map(|(k, v)| Self::show_pkg(k, v))
-                            .join("; ")
+                            .map(|(k, v)| format!("    - {}", Self::show_pkg(k, v)))
+                            .join("\n")
                     )
                 }
             }
@@ -155,6 +156,12 @@ where
             return b;
         }
 
+        if self_linum.is_none() && other_linum.is_none() {
+            if let Some(b) = self.show_combine_dependencies(other) {
+                return b;
+            }
+        }
+
         let mut buf = self.show();
         if let Some(l) = self_linum {
             buf.push_str(" (");
@@ -185,6 +192,30 @@ where
             None
         }
     }
+
+    /// When both incompatibilities express dependencies of the same package, group them into a
+    /// single sentence rather than repeating the depender.
+    fn show_combine_dependencies(&self, other: &Self) -> Option<String> {
+        if self.cause != IncompatibilityCause::Dependency
+            || other.cause != IncompatibilityCause::Dependency
+        {
+            return None;
+        }
+
+        let depender = self.deps.get_index(0)?;
+        if other.deps.get_index(0)? != depender {
+            return None;
+        }
+
+        let left = self.deps.get_index(1)?;
+        let right = other.deps.get_index(1)?;
+        Some(format!(
+            "{} depends on both {} and {}",
+            Self::show_pkg(depender.0, depender.1),
+            Self::show_pkg(left.0, &left.1.complement()),
+            Self::show_pkg(right.0, &right.1.complement())
+        ))
+    }
 }
 
 impl<P> fmt::Debug for Incompatibility<P>
@@ -203,3 +234,60 @@ where
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        package::retriever::PackageId,
+        semver::{Constraint, Version},
+    };
+
+    fn pkg(name: &str) -> PackageId {
+        PackageId::Pkg(name.parse().unwrap())
+    }
+
+    fn exactly(major: u64, minor: u64, patch: u64) -> Constraint {
+        Version::new(major, minor, patch).into()
+    }
+
+    #[test]
+    fn test_show_derived_lists_each_package() {
+        colored::control::set_override(false);
+
+        let inc = Incompatibility::new(
+            indexmap!(
+                pkg("elm/core") => exactly(1, 0, 0),
+                pkg("elm/json") => exactly(1, 1, 3),
+                pkg("elm/http") => exactly(2, 0, 0),
+            ),
+            IncompatibilityCause::Derived(0, 1),
+        );
+
+        assert_eq!(
+            inc.show(),
+            "these 3 requirements cannot all be satisfied together:\n    \
+             - elm/core 1.0.0\n    \
+             - elm/json 1.1.3\n    \
+             - elm/http 2.0.0"
+        );
+    }
+
+    #[test]
+    fn test_show_combine_groups_same_depender() {
+        colored::control::set_override(false);
+
+        let depender = Summary::new(pkg("elm/http"), Version::new(2, 0, 0));
+        let left = Incompatibility::from_dep(
+            depender.clone(),
+            (pkg("elm/core"), exactly(1, 0, 0).complement()),
+        );
+        let right =
+            Incompatibility::from_dep(depender, (pkg("elm/json"), exactly(1, 1, 3).complement()));
+
+        assert_eq!(
+            left.show_combine(&right, None, None),
+            "elm/http 2.0.0 depends on both elm/core 1.0.0 and elm/json 1.1.3"
+        );
+    }
+}
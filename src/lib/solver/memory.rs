@@ -0,0 +1,121 @@
+//! An in-memory `Retriever`, so the resolver can be tested without talking to
+//! package.elm-lang.org.
+
+use super::{
+    incompat::{Incompatibility, IncompatibilityCause},
+    retriever::Retriever,
+    summary::{self, Summary},
+};
+use crate::{
+    package,
+    semver::{Constraint, Version},
+};
+use anyhow::{anyhow, Result};
+use indexmap::indexmap;
+use std::{collections::HashMap, fmt};
+
+#[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Pkg(pub &'static str);
+
+impl Pkg {
+    const ROOT: Self = Self("root");
+}
+
+impl summary::PackageId for Pkg {
+    fn is_root(&self) -> bool {
+        *self == Self::ROOT
+    }
+}
+
+impl fmt::Display for Pkg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Dependencies are given as `(name, range)` pairs, where the range uses the elm.json syntax, e.g.
+/// `("a", "1.0.0 <= v < 2.0.0")`.
+pub type Deps = Vec<(&'static str, &'static str)>;
+
+#[derive(Default)]
+pub struct MemoryRetriever {
+    versions: HashMap<Pkg, Vec<Version>>,
+    deps: HashMap<Summary<Pkg>, Vec<Incompatibility<Pkg>>>,
+}
+
+impl MemoryRetriever {
+    /// Creates a retriever for a root depending on `root`, with `packages` mapping every available
+    /// `(name, version)` to its dependencies.
+    pub fn new(root: Deps, packages: HashMap<(&'static str, &'static str), Deps>) -> Self {
+        let mut retriever = Self::default();
+        retriever.insert(Pkg::ROOT, Version::new(1, 0, 0), root);
+
+        for ((name, version), deps) in packages {
+            let version = version.parse().expect("Invalid version");
+            retriever.insert(Pkg(name), version, deps);
+        }
+
+        for versions in retriever.versions.values_mut() {
+            versions.sort();
+        }
+
+        retriever
+    }
+
+    /// Mark a known version as unavailable, like a version whose elm.json can't be downloaded.
+    pub fn unavailable(&mut self, name: &'static str, version: &str) {
+        let summary = Summary::new(Pkg(name), version.parse().expect("Invalid version"));
+        let incompat = Incompatibility::new(
+            indexmap!(summary.id.clone() => summary.version.into()),
+            IncompatibilityCause::Unavailable,
+        );
+        self.deps.insert(summary, vec![incompat]);
+    }
+
+    fn insert(&mut self, pkg: Pkg, version: Version, deps: Deps) {
+        let summary = Summary::new(pkg.clone(), version);
+        let incompats = deps
+            .into_iter()
+            .map(|(name, range)| {
+                let range: package::Range = range.parse().expect("Invalid range");
+                Incompatibility::from_dep(
+                    summary.clone(),
+                    (Pkg(name), range.to_constraint().complement()),
+                )
+            })
+            .collect();
+
+        self.versions.entry(pkg).or_default().push(version);
+        self.deps.insert(summary, incompats);
+    }
+}
+
+impl Retriever for MemoryRetriever {
+    type PackageId = Pkg;
+
+    fn root(&self) -> Summary<Pkg> {
+        Summary::new(Pkg::ROOT, Version::new(1, 0, 0))
+    }
+
+    fn incompats(&mut self, pkg: &Summary<Pkg>) -> Result<Vec<Incompatibility<Pkg>>> {
+        self.deps
+            .get(pkg)
+            .cloned()
+            .ok_or_else(|| anyhow!("No dependencies known for {} {}", pkg.id, pkg.version))
+    }
+
+    fn count_versions(&self, pkg: &Pkg) -> usize {
+        self.versions.get(pkg).map_or(0, Vec::len)
+    }
+
+    fn best(&mut self, pkg: &Pkg, con: &Constraint) -> Result<Version> {
+        self.versions
+            .get(pkg)
+            .ok_or_else(|| anyhow!("Unknown package {}", pkg))?
+            .iter()
+            .rev()
+            .find(|v| con.satisfies(v))
+            .copied()
+            .ok_or_else(|| anyhow!("Failed to find a version for {}", pkg))
+    }
+}
@@ -20,6 +20,8 @@ SOFTWARE.
 */
 pub mod assignment;
 pub mod incompat;
+#[cfg(test)]
+pub mod memory;
 pub mod retriever;
 pub mod summary;
 
@@ -37,11 +39,88 @@ use petgraph::{
     graphmap::{DiGraphMap, NodeTrait},
     Direction,
 };
-use std::{cmp, collections::VecDeque};
+use std::{
+    cmp,
+    collections::{HashMap, VecDeque},
+    fmt,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
 use tracing::{info, trace};
 
 pub type Graph<T> = petgraph::Graph<T, ()>;
 
+/// Conflict resolution reached the root, so no solution exists. Any other error means we failed
+/// to retrieve information about some package.
+#[derive(Debug, Error)]
+#[error("No conflict resolution found")]
+struct NoConflictResolution;
+
+/// Resolution was aborted because it took longer than allowed.
+#[derive(Debug, Error)]
+#[error("I gave up looking for a solution after {} seconds. You can allow more time with --solve-timeout.", .0.as_secs())]
+pub struct Timeout(pub Duration);
+
+/// Resolution failed. Besides the explanation we show to the user, this holds on to every
+/// incompatibility we came across, so the conflict can be walked through one step at a time.
+#[derive(Debug)]
+pub struct Conflict<P: summary::PackageId> {
+    message: String,
+    incompats: Vec<Incompatibility<P>>,
+    root: usize,
+}
+
+impl<P: summary::PackageId> Conflict<P> {
+    pub fn incompats(&self) -> &[Incompatibility<P>] {
+        &self.incompats
+    }
+
+    pub fn root(&self) -> usize {
+        self.root
+    }
+
+    /// Every derived incompatibility leading up to the failure, in the order they follow from one
+    /// another. Steps refer back to earlier steps by their (1-based) number.
+    pub fn steps(&self) -> Vec<String> {
+        let mut numbers = HashMap::new();
+        let mut steps = Vec::new();
+        self.collect_steps(self.root, &mut numbers, &mut steps);
+        steps
+    }
+
+    fn collect_steps(
+        &self,
+        icix: usize,
+        numbers: &mut HashMap<usize, u16>,
+        steps: &mut Vec<String>,
+    ) {
+        if numbers.contains_key(&icix) {
+            return;
+        }
+
+        if let Some((l, r)) = self.incompats[icix].derived() {
+            self.collect_steps(l, numbers, steps);
+            self.collect_steps(r, numbers, steps);
+
+            let (left, right) = (&self.incompats[l], &self.incompats[r]);
+            steps.push(format!(
+                "Because {}, {}.",
+                left.show_combine(right, numbers.get(&l).copied(), numbers.get(&r).copied()),
+                self.incompats[icix].show()
+            ));
+            numbers.insert(icix, steps.len() as u16);
+        }
+    }
+}
+
+impl<P: summary::PackageId> fmt::Display for Conflict<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl<P: summary::PackageId> std::error::Error for Conflict<P> {}
+
 #[derive(Debug)]
 pub struct Resolver<'ret, R: Retriever> {
     step: u16,
@@ -52,6 +131,7 @@ pub struct Resolver<'ret, R: Retriever> {
     incompats: Vec<Incompatibility<R::PackageId>>,
     incompat_ixs: IndexMap<R::PackageId, Vec<usize>>,
     retriever: &'ret mut R,
+    timeout: Option<(Instant, Duration)>,
 }
 
 impl<'ret, R> Resolver<'ret, R>
@@ -75,6 +155,15 @@ where
             decisions,
             derivations,
             retriever,
+            timeout: None,
+        }
+    }
+
+    /// Give up with a `Timeout` error when solving takes longer than `timeout`, if given.
+    pub fn with_timeout(self, timeout: Option<Duration>) -> Self {
+        Self {
+            timeout: timeout.map(|t| (Instant::now(), t)),
+            ..self
         }
     }
 
@@ -82,14 +171,25 @@ where
         let mut s = self;
 
         info!("beginning dependency resolution");
-        let r = s.solve_loop();
-
-        if r.is_err() {
-            info!("solve failed");
-            bail!("{}", &s.pp_error(s.incompats.len() - 1))
-        } else {
-            info!("solve successful");
-            r
+        match s.solve_loop() {
+            Err(e) if e.is::<NoConflictResolution>() => {
+                info!("solve failed");
+                let root = s.incompats.len() - 1;
+                Err(Conflict {
+                    message: s.pp_error(root),
+                    incompats: s.incompats,
+                    root,
+                }
+                .into())
+            }
+            Err(e) => {
+                info!("solve aborted: {}", e);
+                Err(e)
+            }
+            r => {
+                info!("solve successful");
+                r
+            }
         }
     }
 
@@ -100,45 +200,19 @@ where
 
         let mut next = Some(self.retriever.root().id());
         while let Some(n) = next {
-            self.propagate(n)?;
-            next = self.choose_pkg_version();
-        }
-
-        // To build the tree, we're gonna go through all our dependencies and get their deps,
-        // and build our tree with a BFS. It's one last inefficient process before we have our
-        // nice resolution... oh well.
-        let mut tree = petgraph::Graph::new();
-        let mut set = indexmap!();
-        let mut q = VecDeque::new();
-        let root = self.retriever.root();
-        let root_node = tree.add_node(root.clone());
-        set.insert(root, root_node);
-        q.push_back(root_node);
-
-        while let Some(pid) = q.pop_front() {
-            // At this point, we know there has to be dependencies for these packages.
-            let deps = self.retriever.incompats(&tree[pid]).unwrap();
-            for inc in deps {
-                let pkg = inc.deps.get_index(1).unwrap().0;
-                let ver = &self.decisions[pkg];
-                let sum = Summary::new(pkg.clone(), *ver);
-
-                let nix = if set.contains_key(&sum) {
-                    set[&sum]
-                // We don't push to q here because if it's already in the set, the else must
-                // have run before, meaning it's already been in the q.
-                } else {
-                    let nix = tree.add_node(sum.clone());
-                    set.insert(sum, nix);
-                    q.push_back(nix);
-                    nix
-                };
-
-                tree.add_edge(pid, nix, ());
+            if let Some((started, timeout)) = self.timeout {
+                if started.elapsed() > timeout {
+                    return Err(Timeout(timeout).into());
+                }
             }
+            crate::interrupt::check()?;
+            self.propagate(n)?;
+            next = self.choose_pkg_version()?;
         }
 
-        Ok(tree)
+        let decisions = &self.decisions;
+        // At this point, we know every package we encounter has a decision.
+        Ok(build_graph(self.retriever, |pkg| decisions.get(pkg).copied())?.unwrap())
     }
 
     // 1: Unit propagation
@@ -334,7 +408,7 @@ where
             new_incompatibility = true;
         }
 
-        bail!("No conflict resultion found")
+        bail!(NoConflictResolution)
     }
 
     fn backtrack(&mut self, previous_satisfier_level: u16) {
@@ -381,7 +455,7 @@ where
     }
 
     // 3: Decision making
-    fn choose_pkg_version(&mut self) -> Option<R::PackageId> {
+    fn choose_pkg_version(&mut self) -> Result<Option<R::PackageId>, Error> {
         let mut unsatisfied = self
             .derivations
             .iter()
@@ -395,7 +469,7 @@ where
             .collect::<Vec<_>>();
 
         if unsatisfied.is_empty() {
-            None
+            Ok(None)
         } else {
             // We want to find the unsatisfied package with the fewest available versions.
             unsatisfied.sort_by(|a, b| {
@@ -412,8 +486,9 @@ where
             match best {
                 Ok(best) => {
                     let sum = Summary::new(package.0.clone(), best);
-                    // We know the package exists, so unwrapping here is fine
-                    let incompats = self.retriever.incompats(&sum).unwrap();
+                    // We know the package exists, so failing here means we couldn't retrieve
+                    // its dependencies.
+                    let incompats = self.retriever.incompats(&sum)?;
                     let mut conflict = false;
                     for ic in incompats {
                         conflict = conflict
@@ -444,7 +519,7 @@ where
                     }
                 }
             }
-            res
+            Ok(res)
         }
     }
 
@@ -744,6 +819,74 @@ where
     }
 }
 
+/// The node of the root package in a graph built by the resolver, which is always added first. Only
+/// an empty graph has no root.
+pub fn root<T>(g: &Graph<T>) -> Option<petgraph::graph::NodeIndex> {
+    g.node_indices().next()
+}
+
+/// Builds the dependency graph rooted at the retriever's root package, using `version_of` to pick
+/// the version of every package that is encountered along the way.
+///
+/// Returns `Ok(None)` if `version_of` doesn't provide a version for some package, provides one
+/// that doesn't satisfy the constraints of its dependents, or picks an unavailable version.
+pub fn build_graph<R, F>(
+    retriever: &mut R,
+    version_of: F,
+) -> Result<Option<Graph<Summary<R::PackageId>>>, Error>
+where
+    R: Retriever,
+    F: Fn(&R::PackageId) -> Option<Version>,
+{
+    // To build the tree, we're gonna go through all our dependencies and get their deps,
+    // and build our tree with a BFS. It's one last inefficient process before we have our
+    // nice resolution... oh well.
+    let mut tree = petgraph::Graph::new();
+    let mut set = indexmap!();
+    let mut q = VecDeque::new();
+    let root = retriever.root();
+    let root_node = tree.add_node(root.clone());
+    set.insert(root, root_node);
+    q.push_back(root_node);
+
+    while let Some(pid) = q.pop_front() {
+        let deps = retriever.incompats(&tree[pid])?;
+        for inc in deps {
+            // Only unavailable versions are incompatible with nothing but themselves.
+            let (pkg, con) = match inc.deps.get_index(1) {
+                Some(dep) => dep,
+                None => {
+                    trace!(package = %tree[pid].id, "Unavailable version while building graph");
+                    return Ok(None);
+                }
+            };
+            let ver = match version_of(pkg) {
+                Some(ver) if !con.satisfies(&ver) => ver,
+                _ => {
+                    trace!(package = %pkg, "No valid version available while building graph");
+                    return Ok(None);
+                }
+            };
+            let sum = Summary::new(pkg.clone(), ver);
+
+            let nix = if set.contains_key(&sum) {
+                set[&sum]
+            // We don't push to q here because if it's already in the set, the else must
+            // have run before, meaning it's already been in the q.
+            } else {
+                let nix = tree.add_node(sum.clone());
+                set.insert(sum, nix);
+                q.push_back(nix);
+                nix
+            };
+
+            tree.add_edge(pid, nix, ());
+        }
+    }
+
+    Ok(Some(tree))
+}
+
 fn get_two<T: NodeTrait, E>(graph: &DiGraphMap<T, E>, root: T) -> Option<(T, T)> {
     let xs = graph
         .neighbors_directed(root, Direction::Outgoing)
@@ -756,3 +899,115 @@ fn get_two<T: NodeTrait, E>(graph: &DiGraphMap<T, E>, root: T) -> Option<(T, T)>
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        memory::{MemoryRetriever, Pkg},
+        Conflict, Resolver,
+    };
+    use crate::semver::Version;
+    use std::collections::{BTreeMap, HashMap};
+
+    fn solve(retriever: &mut MemoryRetriever) -> anyhow::Result<BTreeMap<Pkg, Version>> {
+        let g = Resolver::new(retriever).solve()?;
+        Ok(g.raw_nodes()
+            .iter()
+            .map(|n| (n.weight.id.clone(), n.weight.version))
+            .filter(|(id, _)| *id != Pkg("root"))
+            .collect())
+    }
+
+    #[test]
+    fn picks_highest_versions() {
+        let mut retriever = MemoryRetriever::new(
+            vec![("a", "1.0.0 <= v < 2.0.0")],
+            HashMap::from([
+                (("a", "1.0.0"), vec![]),
+                (("a", "1.1.0"), vec![("b", "1.0.0 <= v < 2.0.0")]),
+                (("a", "2.0.0"), vec![]),
+                (("b", "1.0.0"), vec![]),
+                (("b", "1.2.0"), vec![]),
+            ]),
+        );
+
+        let solution = solve(&mut retriever).unwrap();
+        assert_eq!(solution[&Pkg("a")], Version::new(1, 1, 0));
+        assert_eq!(solution[&Pkg("b")], Version::new(1, 2, 0));
+    }
+
+    #[test]
+    fn backtracks_on_conflict() {
+        let mut retriever = MemoryRetriever::new(
+            vec![("a", "1.0.0 <= v < 3.0.0"), ("b", "1.0.0 <= v < 2.0.0")],
+            HashMap::from([
+                (("a", "1.0.0"), vec![("b", "1.0.0 <= v < 2.0.0")]),
+                (("a", "2.0.0"), vec![("b", "2.0.0 <= v < 3.0.0")]),
+                (("b", "1.0.0"), vec![]),
+                (("b", "2.0.0"), vec![]),
+            ]),
+        );
+
+        let solution = solve(&mut retriever).unwrap();
+        assert_eq!(solution[&Pkg("a")], Version::new(1, 0, 0));
+        assert_eq!(solution[&Pkg("b")], Version::new(1, 0, 0));
+    }
+
+    #[test]
+    fn skips_unavailable_versions() {
+        let mut retriever = MemoryRetriever::new(
+            vec![("a", "1.0.0 <= v < 2.0.0")],
+            HashMap::from([(("a", "1.0.0"), vec![]), (("a", "1.1.0"), vec![])]),
+        );
+        retriever.unavailable("a", "1.1.0");
+
+        let solution = solve(&mut retriever).unwrap();
+        assert_eq!(solution[&Pkg("a")], Version::new(1, 0, 0));
+    }
+
+    #[test]
+    fn explains_missing_resolution() {
+        colored::control::set_override(false);
+        let mut retriever = MemoryRetriever::new(
+            vec![("a", "1.0.0 <= v < 2.0.0"), ("b", "1.0.0 <= v < 2.0.0")],
+            HashMap::from([
+                (("a", "1.0.0"), vec![("c", "1.0.0 <= v < 2.0.0")]),
+                (("b", "1.0.0"), vec![("c", "2.0.0 <= v < 3.0.0")]),
+                (("c", "1.0.0"), vec![]),
+                (("c", "2.0.0"), vec![]),
+            ]),
+        );
+
+        let message = solve(&mut retriever).unwrap_err().to_string();
+        assert!(message.contains("a 1.0.0 depends on c 1.0.0 <= v < 2.0.0"));
+        assert!(message
+            .trim_end()
+            .ends_with("no valid set of package versions could be found."));
+    }
+
+    #[test]
+    fn conflict_steps_refer_to_earlier_steps() {
+        colored::control::set_override(false);
+        let mut retriever = MemoryRetriever::new(
+            vec![("a", "1.0.0 <= v < 2.0.0"), ("b", "1.0.0 <= v < 2.0.0")],
+            HashMap::from([
+                (("a", "1.0.0"), vec![("c", "1.0.0 <= v < 2.0.0")]),
+                (("b", "1.0.0"), vec![("c", "2.0.0 <= v < 3.0.0")]),
+                (("c", "1.0.0"), vec![]),
+                (("c", "2.0.0"), vec![]),
+            ]),
+        );
+
+        let err = solve(&mut retriever).unwrap_err();
+        let conflict = err.downcast_ref::<Conflict<Pkg>>().unwrap();
+        let steps = conflict.steps();
+
+        assert!(steps.len() > 1);
+        assert!(steps[0].starts_with("Because "));
+        assert!(steps[1..].iter().any(|step| step.contains("(1)")));
+        assert!(steps
+            .last()
+            .unwrap()
+            .ends_with("no valid set of package versions could be found."));
+    }
+}
@@ -7,7 +7,9 @@ pub struct Summary<P: PackageId> {
     pub version: Version,
 }
 
-pub trait PackageId: Clone + std::hash::Hash + Eq + fmt::Display + Ord {
+pub trait PackageId:
+    Clone + std::hash::Hash + Eq + fmt::Display + fmt::Debug + Ord + Send + Sync + 'static
+{
     fn is_root(&self) -> bool;
 }
 
@@ -0,0 +1,40 @@
+mod common;
+
+use assert_cmd::prelude::*;
+use common::elm_json_offline;
+use predicates::prelude::*;
+use std::error::Error;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn available_version() -> TestResult {
+    let mut cmd = elm_json_offline("available")?;
+    cmd.arg("elm/core@1.0.2");
+    cmd.assert().success().stdout(predicate::str::is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn unavailable_version() -> TestResult {
+    let mut cmd = elm_json_offline("available")?;
+    cmd.arg("elm/core@2.0.0");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn available_as_json() -> TestResult {
+    let mut cmd = elm_json_offline("available")?;
+    cmd.arg("--json").arg("elm/json@1.0.0");
+    cmd.assert().failure().stdout(predicate::str::contains(
+        "{\"available\":false,\"package\":\"elm/json\",\"version\":\"1.0.0\"}",
+    ));
+
+    Ok(())
+}
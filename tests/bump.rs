@@ -0,0 +1,77 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::path::Path;
+use std::{error::Error, process::Command};
+
+fn elm_json_bump() -> Result<Command, Box<dyn Error>> {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.arg("bump");
+    Ok(cmd)
+}
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn bump_package() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = elm_json_bump()?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("minor");
+    cmd.assert().success();
+
+    temp.child("elm.json")
+        .assert(predicate::str::contains("\"version\": \"1.1.0\""));
+
+    Ok(())
+}
+
+#[test]
+fn bump_application() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_application.elm.json"))?;
+
+    let mut cmd = elm_json_bump()?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("patch");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("NOT SUPPORTED"));
+
+    Ok(())
+}
+
+#[test]
+fn bump_application_with_invalid_dependencies() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json").write_str(
+        r#"{
+    "type": "application",
+    "source-directories": [
+        "src"
+    ],
+    "elm-version": "0.19.1",
+    "dependencies": {
+        "direct": {
+            "elm/core": "not a version"
+        },
+        "indirect": {}
+    },
+    "test-dependencies": {
+        "direct": {},
+        "indirect": {}
+    }
+}"#,
+    )?;
+
+    let mut cmd = elm_json_bump()?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("patch");
+    cmd.assert().failure().stderr(
+        predicate::str::contains("NOT SUPPORTED")
+            .and(predicate::str::contains("only the elm.json of a package")),
+    );
+
+    Ok(())
+}
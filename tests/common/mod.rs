@@ -0,0 +1,23 @@
+// Each test crate pulls in this module but only uses part of it.
+#![allow(dead_code)]
+
+use assert_cmd::prelude::*;
+use std::path::{Path, PathBuf};
+use std::{error::Error, process::Command};
+
+pub fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// Run `sub_command` against the fixture registry and package cache, without touching the network.
+pub fn elm_json_offline(sub_command: &str) -> Result<Command, Box<dyn Error>> {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.env("ELM_HOME", fixture("elm_home"))
+        .arg("--offline")
+        .arg("--registry-file")
+        .arg(fixture("registry.json"))
+        .arg(sub_command);
+    Ok(cmd)
+}
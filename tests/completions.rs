@@ -0,0 +1,54 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::{error::Error, process::Command};
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn completions_to_stdout() -> TestResult {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.arg("completions").arg("powershell");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("elm-json"));
+
+    Ok(())
+}
+
+#[test]
+fn completions_to_out_dir() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.arg("completions")
+        .arg("--out-dir")
+        .arg(temp.child("completions").path())
+        .arg("bash");
+    cmd.assert().success().stdout(predicate::str::is_empty());
+
+    temp.child("completions/elm-json.bash")
+        .assert(predicate::path::is_file());
+
+    Ok(())
+}
+
+#[test]
+fn completions_for_all_shells() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.arg("completions")
+        .arg("--all")
+        .arg("--out-dir")
+        .arg(temp.child("completions").path());
+    cmd.assert().success();
+
+    for file in ["elm-json.bash", "_elm-json", "elm-json.fish"] {
+        temp.child("completions")
+            .child(file)
+            .assert(predicate::path::is_file());
+    }
+
+    Ok(())
+}
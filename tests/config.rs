@@ -0,0 +1,73 @@
+mod common;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use common::fixture;
+use predicates::prelude::*;
+use std::path::Path;
+use std::{error::Error, process::Command};
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn config_provides_defaults() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+    temp.child("elm-json.toml").write_str(&format!(
+        "offline = true\nregistry-file = {:?}\nindent = 2\n",
+        fixture("registry.json")
+    ))?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", fixture("elm_home"))
+        .arg("install")
+        .arg("--yes")
+        .arg("elm/json");
+    cmd.assert().success();
+
+    temp.child("elm.json")
+        .assert(predicate::str::contains("\n  \"name\": "));
+
+    Ok(())
+}
+
+#[test]
+fn flags_override_config() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+    temp.child("elm-json.toml")
+        .write_str("offline = true\nregistry-file = \"does-not-exist.json\"\n")?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", fixture("elm_home"))
+        .arg("--registry-file")
+        .arg(fixture("registry.json"))
+        .arg("solve")
+        .arg("--extra")
+        .arg("elm/json");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"elm/json\":\"1.1.3\""));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_config() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+    temp.child("elm-json.toml").write_str("ofline = true\n")?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path()).arg("solve");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("elm-json.toml"));
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use common::elm_json_offline;
+use predicates::prelude::*;
+use std::error::Error;
+use std::path::Path;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn convert_application_to_package() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("convert")?;
+    cmd.current_dir(temp.path())
+        .arg("--to")
+        .arg("package")
+        .arg("--name")
+        .arg("author/project")
+        .arg("--summary")
+        .arg("A prototype, now a package")
+        .arg("--license")
+        .arg("MIT")
+        .arg("--yes");
+    cmd.assert().success();
+
+    temp.child("elm.json").assert(
+        predicate::str::contains("\"type\": \"package\"")
+            .and(predicate::str::contains("\"name\": \"author/project\""))
+            .and(predicate::str::contains(
+                "\"elm/json\": \"1.1.3 <= v < 2.0.0\"",
+            ))
+            .and(predicate::str::contains(
+                "\"elm/core\": \"1.0.5 <= v < 2.0.0\"",
+            )),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn convert_application_with_invalid_license() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("convert")?;
+    cmd.current_dir(temp.path())
+        .arg("--to")
+        .arg("package")
+        .arg("--name")
+        .arg("author/project")
+        .arg("--summary")
+        .arg("A prototype")
+        .arg("--license")
+        .arg("Not-A-License")
+        .arg("--yes");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("not a valid SPDX license"));
+
+    temp.child("elm.json")
+        .assert(predicate::path::eq_file(Path::new(
+            "tests/fixtures/application.elm.json",
+        )));
+
+    Ok(())
+}
+
+#[test]
+fn convert_package_to_application() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/package_with_test_deps.elm.json"))?;
+
+    let mut cmd = elm_json_offline("convert")?;
+    cmd.current_dir(temp.path())
+        .arg("--to")
+        .arg("application")
+        .arg("--yes");
+    cmd.assert().success();
+
+    let written = std::fs::read_to_string(temp.child("elm.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&written)?;
+    assert_eq!(json["type"], "application");
+    assert_eq!(json["dependencies"]["direct"]["elm/core"], "1.0.5");
+    assert_eq!(json["test-dependencies"]["direct"]["elm/json"], "1.1.3");
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{error::Error, process::Command};
+
+fn elm_json_info() -> Result<Command, Box<dyn Error>> {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.arg("info");
+    Ok(cmd)
+}
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn info_application() -> TestResult {
+    let mut cmd = elm_json_info()?;
+    cmd.arg("tests/fixtures/application.elm.json");
+    cmd.assert().success().stdout(
+        predicate::str::contains("Type:                        application\n")
+            .and(predicate::str::contains("Direct dependencies:         1\n"))
+            .and(predicate::str::contains("Indirect dependencies:       1\n")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn info_package_as_json() -> TestResult {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.arg("--json")
+        .arg("info")
+        .arg("tests/fixtures/package_with_test_deps.elm.json");
+    cmd.assert().success().stdout(
+        predicate::str::contains("\"type\":\"package\"")
+            .and(predicate::str::contains("\"name\":\"foo/bar\""))
+            .and(predicate::str::contains("\"test-dependencies\":1")),
+    );
+
+    Ok(())
+}
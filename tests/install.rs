@@ -1,5 +1,8 @@
+mod common;
+
 use assert_cmd::prelude::*;
 use assert_fs::prelude::*;
+use common::{elm_json_offline, fixture};
 use predicates::prelude::*;
 use std::path::Path;
 use std::{error::Error, process::Command};
@@ -24,6 +27,39 @@ fn no_elm_json() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn no_elm_json_as_json() -> TestResult {
+    let mut cmd = elm_json_install()?;
+    cmd.arg("--json")
+        .arg("elm/core")
+        .arg("--")
+        .arg("foo/elm.json");
+
+    cmd.assert().failure().stderr(
+        predicate::str::contains("\"kind\":\"MissingElmJson\"")
+            .and(predicate::str::contains("\"error\":\"missing elm.json\"")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn legacy_elm_json() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/legacy.elm-package.json"))?;
+
+    let mut cmd = elm_json_install()?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("elm/core");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("INVALID ELM.JSON"))
+        .stderr(predicate::str::contains("elm-upgrade"));
+
+    Ok(())
+}
+
 #[test]
 fn install_on_package_succeeds() -> TestResult {
     let temp = assert_fs::TempDir::new()?;
@@ -55,3 +91,350 @@ fn install_on_application_succeeds() -> TestResult {
 
     Ok(())
 }
+
+#[test]
+fn install_exact_on_package() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("--exact")
+        .arg("elm/core");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(", added as 1.0.5 <= v < 1.0.6"));
+
+    temp.child("elm.json").assert(predicate::str::contains(
+        "\"elm/core\": \"1.0.5 <= v < 1.0.6\"",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn install_warns_without_exposed_modules() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let warning = predicate::str::contains("doesn't expose any modules");
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("elm/core");
+    cmd.assert().success().stdout(warning.clone());
+
+    let package = std::fs::read_to_string(fixture("empty_package.elm.json"))?;
+    temp.child("elm.json").write_str(&package.replace(
+        "\"exposed-modules\": []",
+        "\"exposed-modules\": [\"Foo.Bar\"]",
+    ))?;
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("elm/core");
+    cmd.assert().success().stdout(warning.not());
+
+    Ok(())
+}
+
+#[test]
+fn install_with_indent() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("--indent")
+        .arg("2")
+        .arg("elm/core");
+    cmd.assert().success();
+
+    temp.child("elm.json")
+        .assert(predicate::str::contains("{\n  \"type\": \"package\""));
+
+    // The indentation of the existing file is kept when none is passed explicitly.
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("elm/json");
+    cmd.assert().success();
+
+    temp.child("elm.json")
+        .assert(predicate::str::contains("{\n  \"type\": \"package\""));
+
+    Ok(())
+}
+
+#[test]
+fn install_freeze_on_application() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("--freeze")
+        .arg("elm/core");
+    cmd.assert().success();
+
+    temp.child("elm.json").assert(
+        predicate::str::contains("\"elm/core\": \"1.0.5\"")
+            .and(predicate::str::contains("\"frozen\": true")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn install_noninteractive_without_yes() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path())
+        .env("ELM_JSON_NONINTERACTIVE", "1")
+        .arg("elm/core");
+    cmd.assert().success();
+
+    temp.child("elm.json")
+        .assert(predicate::str::contains("\"elm/core\": \"1.0.5\""));
+
+    Ok(())
+}
+
+#[test]
+fn install_lax_zero_major() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("elm/core@0");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Please pick a minor version too"));
+
+    Ok(())
+}
+
+#[test]
+fn install_frozen_without_changes() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path()).arg("--frozen").arg("elm/json");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("NO CHANGES REQUIRED"));
+
+    Ok(())
+}
+
+#[test]
+fn install_frozen_with_changes() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path()).arg("--frozen").arg("elm/core");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("elm/core"))
+        .stderr(predicate::str::contains("ELM.JSON IS OUT OF DATE"));
+
+    temp.child("elm.json")
+        .assert(predicate::path::eq_file(Path::new(
+            "tests/fixtures/application.elm.json",
+        )));
+
+    Ok(())
+}
+
+#[test]
+fn install_strict_with_changes() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    // elm/json 1.1.2 doesn't need elm/core, so installing it would drop elm/core.
+    temp.child("registry.json")
+        .write_str(r#"["elm/core@1.0.5", "elm/json@1.1.2", "elm/json@1.1.3"]"#)?;
+    temp.child("elm_home")
+        .copy_from(fixture("elm_home"), &["**"])?;
+    let json =
+        std::fs::read_to_string(fixture("elm_home/0.19.1/packages/elm/json/1.1.3/elm.json"))?;
+    temp.child("elm_home/elm-json/packages/elm/json/1.1.2/elm.json")
+        .write_str(
+            &json
+                .replace("\"1.1.3\"", "\"1.1.2\"")
+                .replace("\"elm/core\": \"1.0.0 <= v < 2.0.0\"", ""),
+        )?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", temp.child("elm_home").path())
+        .arg("--offline")
+        .arg("--registry-file")
+        .arg("registry.json")
+        .arg("install")
+        .arg("--yes")
+        .arg("--strict")
+        .arg("elm/json@1.1.2");
+    cmd.assert().failure().stderr(
+        predicate::str::contains("allowed with --strict")
+            .and(predicate::str::contains("elm/core 1.0.5 would be removed")),
+    );
+
+    temp.child("elm.json")
+        .assert(predicate::path::eq_file(Path::new(
+            "tests/fixtures/application.elm.json",
+        )));
+
+    Ok(())
+}
+
+#[test]
+fn install_dependency_as_test_dependency() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("--test")
+        .arg("elm/json");
+    cmd.assert().failure().stderr(
+        predicate::str::contains("INVALID ARGUMENT")
+            .and(predicate::str::contains("elm/json is already a dependency")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn install_test_dependency_as_dependency() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/package_with_test_deps.elm.json"))?;
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("elm/json");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "elm/json is already a test-dependency",
+    ));
+
+    temp.child("elm.json")
+        .assert(predicate::path::eq_file(Path::new(
+            "tests/fixtures/package_with_test_deps.elm.json",
+        )));
+
+    Ok(())
+}
+
+#[test]
+fn install_by_project_name() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("json");
+    cmd.assert().success();
+
+    temp.child("elm.json").assert(predicate::str::contains(
+        "\"elm/json\": \"1.1.3 <= v < 2.0.0\"",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn install_ambiguous_project_name() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+    temp.child("registry.json")
+        .write_str(r#"["elm/json@1.1.3", "someone/json@1.0.0"]"#)?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", fixture("elm_home"))
+        .arg("--offline")
+        .arg("--registry-file")
+        .arg("registry.json")
+        .arg("install")
+        .arg("--yes")
+        .arg("json");
+    cmd.assert().failure().stderr(
+        predicate::str::contains("There are several packages called json")
+            .and(predicate::str::contains("someone/json")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn install_pinned_indirect() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    let app = std::fs::read_to_string(fixture("application.elm.json"))?;
+    temp.child("elm.json")
+        .write_str(&app.replace("\"1.0.5\"", "\"1.0.0\""))?;
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("--indirect")
+        .arg("elm/core@1.0.5");
+    cmd.assert().success();
+
+    temp.child("elm.json").assert(predicate::str::contains(
+        "\"indirect\": {\n            \"elm/core\": \"1.0.5\"",
+    ));
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("--indirect")
+        .arg("elm/json@1.1.3");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("is a direct dependency"));
+
+    Ok(())
+}
+
+#[test]
+fn install_from_package() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("--from-package")
+        .arg(fixture("package_with_test_deps.elm.json"));
+    cmd.assert().success();
+
+    temp.child("elm.json").assert(
+        predicate::str::contains("\"direct\": {\n            \"elm/core\": \"1.0.5\"")
+            .and(predicate::str::contains("elm/json").not()),
+    );
+
+    let mut cmd = elm_json_offline("install")?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("--from-package")
+        .arg(fixture("application.elm.json"));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("needs a package"));
+
+    Ok(())
+}
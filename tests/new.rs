@@ -0,0 +1,23 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::{error::Error, process::Command};
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn new_noninteractive() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_JSON_NONINTERACTIVE", "1")
+        .arg("new");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("interactive terminal"));
+
+    temp.child("elm.json").assert(predicate::path::missing());
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::path::Path;
+use std::{error::Error, process::Command};
+
+fn elm_json_rename() -> Result<Command, Box<dyn Error>> {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.arg("rename");
+    Ok(cmd)
+}
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn rename_package() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = elm_json_rename()?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("someone-else/fork");
+    cmd.assert().success();
+
+    temp.child("elm.json")
+        .assert(predicate::str::contains("\"name\": \"someone-else/fork\""));
+
+    Ok(())
+}
+
+#[test]
+fn rename_application() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_application.elm.json"))?;
+
+    let mut cmd = elm_json_rename()?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("someone-else/fork");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("NOT SUPPORTED"));
+
+    Ok(())
+}
+
+#[test]
+fn rename_invalid_name() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = elm_json_rename()?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("not-a-name");
+    cmd.assert().failure();
+
+    temp.child("elm.json")
+        .assert(predicate::path::eq_file(Path::new(
+            "tests/fixtures/empty_package.elm.json",
+        )));
+
+    Ok(())
+}
+
+#[test]
+fn rename_replaces_elm_json_without_leftovers() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = elm_json_rename()?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("someone-else/fork");
+    cmd.assert().success();
+
+    let files: Vec<_> = std::fs::read_dir(temp.path())?
+        .map(|entry| entry.map(|e| e.file_name()))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(files, ["elm.json"]);
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use assert_cmd::prelude::*;
+use common::elm_json_offline;
+use predicates::prelude::*;
+use std::error::Error;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn search_finds_latest_version() -> TestResult {
+    let mut cmd = elm_json_offline("search")?;
+    cmd.arg("CORE");
+    cmd.assert().success().stdout("elm/core 1.0.5\n");
+
+    Ok(())
+}
+
+#[test]
+fn search_as_json() -> TestResult {
+    let mut cmd = elm_json_offline("search")?;
+    cmd.arg("--json").arg("elm");
+    cmd.assert()
+        .success()
+        .stdout("{\"elm/core\":\"1.0.5\",\"elm/json\":\"1.1.3\"}\n");
+
+    Ok(())
+}
+
+#[test]
+fn search_without_matches() -> TestResult {
+    let mut cmd = elm_json_offline("search")?;
+    cmd.arg("html");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "I couldn't find any packages matching",
+    ));
+
+    Ok(())
+}
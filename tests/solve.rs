@@ -0,0 +1,664 @@
+mod common;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use common::{elm_json_offline, fixture};
+use predicates::prelude::*;
+use std::path::Path;
+use std::{error::Error, process::Command};
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn solve_with_registry_file() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path()).arg("--extra").arg("elm/json");
+    cmd.assert().success().stdout(
+        predicate::str::contains("\"elm/json\":\"1.1.3\"")
+            .and(predicate::str::contains("\"elm/core\":\"1.0.5\"")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn missing_registry_file() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .arg("--registry-file")
+        .arg("does-not-exist.json")
+        .arg("solve");
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn solve_separate_test() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/package_with_test_deps.elm.json"))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path())
+        .arg("--test")
+        .arg("--separate-test");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "{\"direct\":{\"elm/core\":\"1.0.5\"},\"indirect\":{},\
+         \"test-direct\":{\"elm/json\":\"1.1.3\"},\"test-indirect\":{}}",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn solve_elm_format() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/package_with_test_deps.elm.json"))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path())
+        .arg("--test")
+        .arg("--elm-format");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "{\"elm-version\":\"0.19.1\",\
+         \"dependencies\":{\"direct\":{\"elm/core\":\"1.0.5\"},\"indirect\":{}},\
+         \"test-dependencies\":{\"direct\":{\"elm/json\":\"1.1.3\"},\"indirect\":{}}}",
+    ));
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path()).arg("--elm-format");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--test"));
+
+    Ok(())
+}
+
+#[test]
+fn solve_include_elm() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path())
+        .arg("--include-elm")
+        .arg("--extra")
+        .arg("elm/json");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("{\"elm-version\":\"0.19.1\","));
+
+    Ok(())
+}
+
+#[test]
+fn solve_prefer_cached() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", fixture("elm_home"))
+        .arg("--offline")
+        .arg("--prefer-cached")
+        .arg("--registry-file")
+        .arg(fixture("registry.json"))
+        .arg("solve")
+        .arg("--minimize")
+        .arg("--extra")
+        .arg("elm/core");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"elm/core\":\"1.0.5\""));
+
+    Ok(())
+}
+
+#[test]
+fn solve_multiple_files() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("app/elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+    temp.child("pkg/elm.json")
+        .write_file(Path::new("tests/fixtures/package_with_test_deps.elm.json"))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path())
+        .arg("app/elm.json")
+        .arg("pkg/elm.json");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "{\"app/elm.json\":{\"direct\":{\"elm/json\":\"1.1.3\"},\"indirect\":{\"elm/core\":\"1.0.5\"}},\
+         \"pkg/elm.json\":{\"direct\":{\"elm/core\":\"1.0.5\"},\"indirect\":{}}}",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn solve_explain_conflict() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json").write_str(
+        r#"{
+    "type": "package",
+    "name": "author/project",
+    "summary": "helpful summary of your project, less than 80 characters",
+    "license": "BSD-3-Clause",
+    "version": "1.0.0",
+    "exposed-modules": [],
+    "elm-version": "0.19.0 <= v < 0.20.0",
+    "dependencies": {
+        "elm/json": "1.0.0 <= v < 1.1.0"
+    },
+    "test-dependencies": {}
+}"#,
+    )?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", fixture("elm_home"))
+        .arg("--offline")
+        .arg("--registry-file")
+        .arg(fixture("registry.json"))
+        .arg("--explain")
+        .arg("solve");
+    cmd.assert().failure().stderr(
+        predicate::str::contains("CONFLICT, STEP BY STEP")
+            .and(predicate::str::contains("(1) Because")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn solve_malformed_extra() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .arg("solve")
+        .arg("--extra")
+        .arg("elm/core@1.0.0@2.0.0");
+    cmd.assert().failure().stderr(
+        predicate::str::contains("Expected a package name")
+            .and(predicate::str::contains("panicked").not()),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn solve_extra_past_validation() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    // A well-formed lax version, but there is no next major version to bound it with.
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path())
+        .arg("--extra")
+        .arg("elm/core@18446744073709551615");
+    cmd.assert().failure().stderr(
+        predicate::str::contains("make sense of the package")
+            .and(predicate::str::contains("panicked").not()),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn solve_check_licenses() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+    temp.child("elm_home")
+        .copy_from(fixture("elm_home"), &["**"])?;
+    let json =
+        std::fs::read_to_string(fixture("elm_home/0.19.1/packages/elm/json/1.1.3/elm.json"))?;
+    temp.child("elm_home/0.19.1/packages/elm/json/1.1.3/elm.json")
+        .write_str(&json.replace("BSD-3-Clause", "GPL-3.0"))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", temp.child("elm_home").path())
+        .arg("--check-licenses");
+    cmd.assert().success().stderr(
+        predicate::str::contains("copyleft license")
+            .and(predicate::str::contains("- elm/json 1.1.3 (GPL-3.0)"))
+            .and(predicate::str::contains("elm/core").not()),
+    );
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", temp.child("elm_home").path())
+        .arg("--check-licenses")
+        .arg("--allow-license")
+        .arg("GPL-3.0");
+    cmd.assert().success().stderr("");
+
+    Ok(())
+}
+
+#[test]
+fn solve_unreachable_registry_without_cache() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+    temp.child("elm_home").create_dir_all()?;
+
+    // Nothing listens on port 1, so the package server can't be reached through this proxy.
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", temp.child("elm_home").path())
+        .env("https_proxy", "http://127.0.0.1:1")
+        .env("HTTPS_PROXY", "http://127.0.0.1:1")
+        .env_remove("no_proxy")
+        .env_remove("NO_PROXY")
+        .arg("--json")
+        .arg("solve");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("\"kind\":\"NetworkError\""));
+
+    Ok(())
+}
+
+#[test]
+fn solve_memoize() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/package_with_test_deps.elm.json"))?;
+    temp.child("elm_home")
+        .copy_from(fixture("elm_home"), &["**"])?;
+
+    for memoized in &[false, true] {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+        cmd.current_dir(temp.path())
+            .env("ELM_HOME", temp.child("elm_home").path())
+            .arg("-vv")
+            .arg("--offline")
+            .arg("--memoize")
+            .arg("--registry-file")
+            .arg(fixture("registry.json"))
+            .arg("solve");
+        // At -vv, the debug log ends up on stdout next to the solution.
+        let solution =
+            predicate::str::ends_with("{\"direct\":{\"elm/core\":\"1.0.5\"},\"indirect\":{}}\n");
+        let reused = predicate::str::contains("Reusing memoized solution");
+        if *memoized {
+            cmd.assert().success().stdout(solution.and(reused));
+        } else {
+            cmd.assert().success().stdout(solution.and(reused.not()));
+        }
+    }
+
+    temp.child("elm_home/elm-json/solutions")
+        .assert(predicate::path::is_dir());
+
+    Ok(())
+}
+
+#[test]
+fn invalid_registry_entry() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+    temp.child("registry.json")
+        .write_str(r#"["elm/core@1.0.0", "elm/json@1.x.3"]"#)?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", fixture("elm_home"))
+        .arg("--offline")
+        .arg("--registry-file")
+        .arg("registry.json")
+        .arg("solve");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Invalid registry entry elm/json@1.x.3 for elm/json: Invalid version: 1.x.3",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn solve_conflicting_extras() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path())
+        .arg("--extra")
+        .arg("elm/core@1.0.0")
+        .arg("elm/core@1.0.5");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "You asked for elm/core at both 1.0.0 and 1.0.5",
+    ));
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path())
+        .arg("--extra")
+        .arg("elm/core@1.0.5")
+        .arg("elm/core");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"elm/core\":\"1.0.5\""));
+
+    Ok(())
+}
+
+#[test]
+fn solve_locked() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+    temp.child("elm_home").create_dir_all()?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", temp.child("elm_home").path())
+        .arg("--locked")
+        .arg("solve");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "I need to download the list of available packages",
+    ));
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", fixture("elm_home"))
+        .arg("--locked")
+        .arg("--registry-file")
+        .arg(fixture("registry.json"))
+        .arg("solve")
+        .arg("--extra")
+        .arg("elm/core@1.0.0");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "I need to download the elm.json for elm/core@1.0.0",
+    ));
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", fixture("elm_home"))
+        .arg("--locked")
+        .arg("--registry-file")
+        .arg(fixture("registry.json"))
+        .arg("solve")
+        .arg("--extra")
+        .arg("elm/json");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"elm/json\":\"1.1.3\""));
+
+    Ok(())
+}
+
+#[test]
+fn solve_json_logs() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/package_with_test_deps.elm.json"))?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", fixture("elm_home"))
+        .arg("-vv")
+        .arg("--log-format")
+        .arg("json")
+        .arg("--offline")
+        .arg("--registry-file")
+        .arg(fixture("registry.json"))
+        .arg("solve");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "\"level\":\"DEBUG\",\"fields\":{\"message\":\"Reading versions from",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn solve_pretty() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/package_with_test_deps.elm.json"))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path()).arg("--pretty");
+    cmd.assert()
+        .success()
+        .stdout("{\n  \"direct\": {\n    \"elm/core\": \"1.0.5\"\n  },\n  \"indirect\": {}\n}\n");
+
+    Ok(())
+}
+
+#[test]
+fn solve_no_elm() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    let app = std::fs::read_to_string(fixture("application.elm.json"))?;
+    temp.child("elm.json")
+        .write_str(&app.replace("\"0.19.1\"", "\"0.18.0\""))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path());
+    cmd.assert().failure();
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path()).arg("--no-elm");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"elm/json\":\"1.1.3\""));
+
+    Ok(())
+}
+
+#[test]
+fn solve_mismatched_cached_elm_json() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+    temp.child("elm_home")
+        .copy_from(fixture("elm_home"), &["**"])?;
+    temp.child("elm_home/elm-json/packages/elm/core/1.0.0/elm.json")
+        .write_file(&fixture("elm_home/0.19.1/packages/elm/core/1.0.5/elm.json"))?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", temp.child("elm_home").path())
+        .arg("--offline")
+        .arg("--registry-file")
+        .arg(fixture("registry.json"))
+        .arg("solve")
+        .arg("--extra")
+        .arg("elm/core@1.0.0");
+    cmd.assert().failure();
+
+    temp.child("elm_home/elm-json/packages/elm/core/1.0.0/elm.json")
+        .assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn solve_prefer() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path())
+        .arg("--minimize")
+        .arg("--prefer")
+        .arg("elm/core@1.0.5")
+        .arg("--extra")
+        .arg("elm/core");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"elm/core\":\"1.0.5\""));
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path())
+        .arg("--prefer")
+        .arg("elm/core@1.0.0")
+        .arg("--extra")
+        .arg("elm/core@1.0.5");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"elm/core\":\"1.0.5\""));
+
+    Ok(())
+}
+
+#[test]
+fn solve_timeout() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/package_with_test_deps.elm.json"))?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", fixture("elm_home"))
+        .arg("--offline")
+        .arg("--json")
+        .arg("--solve-timeout")
+        .arg("0")
+        .arg("--registry-file")
+        .arg(fixture("registry.json"))
+        .arg("solve");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("\"kind\":\"Timeout\""));
+
+    Ok(())
+}
+
+#[test]
+fn solve_minimize_package() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_package.elm.json"))?;
+    temp.child("elm_home")
+        .copy_from(fixture("elm_home"), &["**"])?;
+    let core =
+        std::fs::read_to_string(fixture("elm_home/0.19.1/packages/elm/core/1.0.5/elm.json"))?;
+    temp.child("elm_home/elm-json/packages/elm/core/1.0.0/elm.json")
+        .write_str(&core.replace("\"1.0.5\"", "\"1.0.0\""))?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", temp.child("elm_home").path())
+        .arg("--offline")
+        .arg("--registry-file")
+        .arg(fixture("registry.json"))
+        .arg("solve")
+        .arg("--minimize-package")
+        .arg("elm/core")
+        .arg("--extra")
+        .arg("elm/core")
+        .arg("elm/json");
+    cmd.assert().success().stdout(
+        predicate::str::contains("\"elm/core\":\"1.0.0\"")
+            .and(predicate::str::contains("\"elm/json\":\"1.1.3\"")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn solve_unknown_packages() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    let app = std::fs::read_to_string(fixture("application.elm.json"))?;
+    temp.child("elm.json").write_str(&app.replace(
+        "\"elm/json\": \"1.1.3\"",
+        "\"elm/json\": \"1.1.3\", \"elm/htlm\": \"1.0.0\", \"elm/jsno\": \"1.0.0\"",
+    ))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path());
+    cmd.assert().failure().stderr(
+        predicate::str::contains("- elm/htlm\n- elm/jsno")
+            .and(predicate::str::contains("INVALID ELM.JSON")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn solve_allow_comments() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    let package = std::fs::read_to_string(fixture("empty_package.elm.json"))?;
+    temp.child("elm.json")
+        .write_str(&format!("// Annotated\n{}", package))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("INVALID ELM.JSON"));
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path()).arg("--allow-comments");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("{\"direct\":{},\"indirect\":{}}"));
+
+    Ok(())
+}
+
+#[test]
+fn solve_unknown_elm_version() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    let app = std::fs::read_to_string(fixture("application.elm.json"))?;
+    temp.child("elm.json")
+        .write_str(&app.replace("0.19.1", "0.19.2"))?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Elm 0.19.2"))
+        .stderr(predicate::str::contains("0.18.0, 0.19.0"));
+
+    Ok(())
+}
+
+#[test]
+fn solve_without_test_leaves_out_test_only_packages() -> TestResult {
+    // elm/json is preferred from the stale dependencies.indirect, but nothing in production
+    // needs it.
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json").write_str(
+        r#"{
+    "type": "application",
+    "source-directories": ["src"],
+    "elm-version": "0.19.1",
+    "dependencies": {
+        "direct": {"elm/core": "1.0.5"},
+        "indirect": {"elm/json": "1.1.3"}
+    },
+    "test-dependencies": {
+        "direct": {"elm/json": "1.1.3"},
+        "indirect": {}
+    }
+}"#,
+    )?;
+
+    let mut cmd = elm_json_offline("solve")?;
+    cmd.current_dir(temp.path());
+    cmd.assert().success().stdout(predicate::str::contains(
+        "{\"direct\":{\"elm/core\":\"1.0.5\"},\"indirect\":{}}",
+    ));
+
+    Ok(())
+}
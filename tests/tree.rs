@@ -0,0 +1,181 @@
+mod common;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use common::{elm_json_offline, fixture};
+use predicates::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+use std::{error::Error, process::Command};
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn tree_of_pinned_application_needs_no_versions() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+    temp.child("registry.json").write_str("[]")?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", fixture("elm_home"))
+        .arg("--offline")
+        .arg("--registry-file")
+        .arg("registry.json")
+        .arg("tree");
+    cmd.assert().success().stdout(
+        predicate::str::contains("elm/json @ 1.1.3")
+            .and(predicate::str::contains("elm/core @ 1.0.5")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_ascii() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("tree")?;
+    cmd.current_dir(temp.path()).arg("--ascii");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "`-- elm/json @ 1.1.3\n    `-- elm/core @ 1.0.5",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn tree_dot() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("tree")?;
+    cmd.current_dir(temp.path()).arg("--dot");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output)?;
+    assert!(output.starts_with("digraph {"));
+
+    // Node indices are an implementation detail, so compare the edges by their labels.
+    let mut labels = HashMap::new();
+    let mut edges = Vec::new();
+    for line in output.lines().map(str::trim) {
+        if let Some((node, label)) = line.split_once(" [ label = ") {
+            labels.insert(node, label.trim_end_matches(" ]").trim_matches('"'));
+        } else if let Some((from, rest)) = line.split_once(" -> ") {
+            let to = rest.split(' ').next().unwrap_or_default();
+            edges.push((from, to));
+        }
+    }
+    let mut edges: Vec<_> = edges
+        .into_iter()
+        .map(|(from, to)| (labels[from], labels[to]))
+        .collect();
+    edges.sort();
+
+    assert_eq!(
+        edges,
+        vec![
+            ("elm/json@1.1.3", "elm/core@1.0.5"),
+            ("project", "elm/json@1.1.3"),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_json() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("tree")?;
+    cmd.current_dir(temp.path()).arg("--json");
+    cmd.assert()
+        .success()
+        .stdout("{\"elm/core\":\"1.0.5\",\"elm/json\":\"1.1.3\"}\n");
+
+    Ok(())
+}
+
+#[test]
+fn tree_without_dependencies() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/empty_application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("tree")?;
+    cmd.current_dir(temp.path());
+    cmd.assert().success().stdout(predicate::str::contains(
+        "project\n\nThis project doesn't have any dependencies.",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn tree_marks_test_dependencies() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/package_with_test_deps.elm.json"))?;
+
+    let mut cmd = elm_json_offline("tree")?;
+    cmd.current_dir(temp.path())
+        .env("NO_COLOR", "1")
+        .arg("--test");
+    cmd.assert().success().stdout(
+        predicate::str::contains("elm/json @ 1.1.3 (test)")
+            .and(predicate::str::contains("elm/core @ 1.0.5 (test)").not()),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_reverse() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("tree")?;
+    cmd.current_dir(temp.path())
+        .arg("--ascii")
+        .arg("--reverse")
+        .arg("elm/core");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "elm/core @ 1.0.5\n`-- elm/json @ 1.1.3\n    `-- project",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn tree_package_with_version() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let tree = |filter: &str| -> Result<Command, Box<dyn Error>> {
+        let mut cmd = elm_json_offline("tree")?;
+        cmd.current_dir(temp.path()).arg(filter);
+        Ok(cmd)
+    };
+
+    tree("elm/core@1.0.5")?
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("elm/core @ 1.0.5"));
+    tree("elm/core@1.0.4")?
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Could not find elm/core@1.0.4 in direct or indirect dependencies.",
+        ));
+    tree("elm/core@latest")?.assert().failure();
+
+    Ok(())
+}
@@ -1,5 +1,8 @@
+mod common;
+
 use assert_cmd::prelude::*;
 use assert_fs::prelude::*;
+use common::elm_json_offline;
 use predicates::prelude::*;
 use std::path::Path;
 use std::{error::Error, process::Command};
@@ -63,3 +66,89 @@ fn uninstall_on_application_succeeds() -> TestResult {
 
     Ok(())
 }
+
+#[test]
+fn uninstall_reports_removed_packages() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("uninstall")?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("elm/json");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("- elm/json was removed entirely"));
+
+    Ok(())
+}
+
+#[test]
+fn uninstall_reports_packages_still_needed() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json").write_str(
+        r#"{
+    "type": "application",
+    "source-directories": [
+        "src"
+    ],
+    "elm-version": "0.19.1",
+    "dependencies": {
+        "direct": {
+            "elm/core": "1.0.5",
+            "elm/json": "1.1.3"
+        },
+        "indirect": {}
+    },
+    "test-dependencies": {
+        "direct": {},
+        "indirect": {}
+    }
+}"#,
+    )?;
+
+    let mut cmd = elm_json_offline("uninstall")?;
+    cmd.current_dir(temp.path()).arg("--yes").arg("elm/core");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "- elm/core is still needed by elm/json, so it stays in elm.json",
+    ));
+
+    temp.child("elm.json").assert(predicate::str::contains(
+        "\"indirect\": {\n            \"elm/core\": \"1.0.5\"",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn uninstall_more_than_max_removals() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("uninstall")?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("--max-removals")
+        .arg("1")
+        .arg("elm/json");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("This removes 2 packages"));
+
+    temp.child("elm.json")
+        .assert(predicate::str::contains("elm/json"));
+
+    let mut cmd = elm_json_offline("uninstall")?;
+    cmd.current_dir(temp.path())
+        .arg("--yes")
+        .arg("--max-removals")
+        .arg("1")
+        .arg("--force")
+        .arg("elm/json");
+    cmd.assert().success();
+
+    temp.child("elm.json")
+        .assert(predicate::str::contains("elm/json").not());
+
+    Ok(())
+}
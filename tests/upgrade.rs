@@ -0,0 +1,133 @@
+mod common;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use common::{elm_json_offline, fixture};
+use predicates::prelude::*;
+use std::path::Path;
+use std::{error::Error, process::Command};
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn upgrade_check_up_to_date() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_offline("upgrade")?;
+    cmd.current_dir(temp.path()).arg("--check");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("PACKAGES UP TO DATE"));
+
+    Ok(())
+}
+
+#[test]
+fn upgrade_check_with_upgrades() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    let app = std::fs::read_to_string(fixture("application.elm.json"))?;
+    temp.child("elm.json")
+        .write_str(&app.replace("\"1.0.5\"", "\"1.0.0\""))?;
+
+    let mut cmd = elm_json_offline("upgrade")?;
+    cmd.current_dir(temp.path()).arg("--check");
+    cmd.assert()
+        .failure()
+        .stdout(
+            predicate::str::contains("PACKAGE UPGRADES FOUND")
+                .and(predicate::str::contains("0 direct, 1 indirect changed.")),
+        )
+        .stderr(predicate::str::contains("without --check"));
+
+    temp.child("elm.json")
+        .assert(predicate::str::contains("\"elm/core\": \"1.0.0\""));
+
+    Ok(())
+}
+
+#[test]
+fn upgrade_drops_test_only_packages_from_indirect() -> TestResult {
+    // elm/json is only needed by the tests, but was left behind in dependencies.indirect.
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json").write_str(
+        r#"{
+    "type": "application",
+    "source-directories": ["src"],
+    "elm-version": "0.19.1",
+    "dependencies": {
+        "direct": {"elm/core": "1.0.5"},
+        "indirect": {"elm/json": "1.1.3"}
+    },
+    "test-dependencies": {
+        "direct": {"elm/json": "1.1.3"},
+        "indirect": {}
+    }
+}"#,
+    )?;
+
+    let mut cmd = elm_json_offline("upgrade")?;
+    cmd.current_dir(temp.path())
+        .arg("--ignore-test")
+        .arg("--yes");
+    cmd.assert().success();
+
+    let written = std::fs::read_to_string(temp.child("elm.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&written)?;
+    assert_eq!(json["dependencies"]["indirect"], serde_json::json!({}));
+    assert_eq!(json["test-dependencies"]["direct"]["elm/json"], "1.1.3");
+
+    Ok(())
+}
+
+#[test]
+fn upgrade_notes_pre_1_0_packages() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    let app = std::fs::read_to_string(fixture("empty_application.elm.json"))?;
+    temp.child("elm.json").write_str(&app.replacen(
+        "\"direct\": {}",
+        "\"direct\": {\"elm/core\": \"1.0.5\", \"author/unstable\": \"0.1.0\"}",
+        1,
+    ))?;
+    temp.child("registry.json")
+        .write_str(r#"["elm/core@1.0.5", "author/unstable@0.1.0", "author/unstable@0.1.1"]"#)?;
+    temp.child("elm_home")
+        .copy_from(fixture("elm_home"), &["**"])?;
+    for version in &["0.1.0", "0.1.1"] {
+        temp.child(format!(
+            "elm_home/elm-json/packages/author/unstable/{}/elm.json",
+            version
+        ))
+        .write_str(&format!(
+            r#"{{
+    "type": "package",
+    "name": "author/unstable",
+    "summary": "Not quite there yet",
+    "license": "BSD-3-Clause",
+    "version": "{}",
+    "exposed-modules": ["Unstable"],
+    "elm-version": "0.19.0 <= v < 0.20.0",
+    "dependencies": {{}},
+    "test-dependencies": {{}}
+}}"#,
+            version
+        ))?;
+    }
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(temp.path())
+        .env("ELM_HOME", temp.child("elm_home").path())
+        .arg("--offline")
+        .arg("--registry-file")
+        .arg("registry.json")
+        .arg("upgrade")
+        .arg("--yes");
+    cmd.assert().success().stdout(
+        predicate::str::contains("still pre-1.0")
+            .and(predicate::str::contains("- author/unstable 0.1.1"))
+            .and(predicate::str::contains("- elm/core").not()),
+    );
+
+    Ok(())
+}
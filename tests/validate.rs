@@ -0,0 +1,89 @@
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::path::Path;
+use std::{error::Error, process::Command};
+
+fn elm_json_validate() -> Result<Command, Box<dyn Error>> {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.arg("validate");
+    Ok(cmd)
+}
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+#[test]
+fn validate_valid_elm_json() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    temp.child("elm.json")
+        .write_file(Path::new("tests/fixtures/application.elm.json"))?;
+
+    let mut cmd = elm_json_validate()?;
+    cmd.current_dir(temp.path()).arg("--all");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("elm.json is valid."));
+
+    Ok(())
+}
+
+#[test]
+fn validate_reports_all_problems() -> TestResult {
+    let temp = assert_fs::TempDir::new()?;
+    let package = std::fs::read_to_string("tests/fixtures/empty_package.elm.json")?;
+    let package: serde_json::Value = serde_json::from_str(&package)?;
+    let mut package = package.as_object().unwrap().clone();
+    package.insert("version".into(), "1.x.0".into());
+    package.insert("license".into(), "Not-A-License".into());
+    temp.child("elm.json")
+        .write_str(&serde_json::to_string(&package)?)?;
+
+    let mut cmd = elm_json_validate()?;
+    cmd.current_dir(temp.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("INVALID ELM.JSON"));
+
+    let mut cmd = elm_json_validate()?;
+    cmd.current_dir(temp.path()).arg("--all");
+    cmd.assert().failure().stderr(
+        predicate::str::contains("I found 2 problem(s)")
+            .and(predicate::str::contains("- license: Not-A-License"))
+            .and(predicate::str::contains("- version: Invalid version")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validate_modes_agree() -> TestResult {
+    let package = std::fs::read_to_string("tests/fixtures/empty_package.elm.json")?;
+    let package: serde_json::Value = serde_json::from_str(&package)?;
+    let package = package.as_object().unwrap();
+
+    let mut bad_license = package.clone();
+    bad_license.insert("license".into(), "Not-A-License".into());
+    let mut case_duplicate = package.clone();
+    case_duplicate.insert(
+        "dependencies".into(),
+        serde_json::json!({"elm/core": "1.0.0 <= v < 2.0.0", "Elm/core": "1.0.0 <= v < 2.0.0"}),
+    );
+
+    for package in [bad_license, case_duplicate] {
+        let temp = assert_fs::TempDir::new()?;
+        temp.child("elm.json")
+            .write_str(&serde_json::to_string(&package)?)?;
+
+        elm_json_validate()?
+            .current_dir(temp.path())
+            .assert()
+            .failure();
+        elm_json_validate()?
+            .current_dir(temp.path())
+            .arg("--all")
+            .assert()
+            .failure();
+    }
+
+    Ok(())
+}